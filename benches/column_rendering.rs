@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra_glm::Vec2;
+use rust_maze3d::door::find_doors;
+use rust_maze3d::floor::extract_floor;
+use rust_maze3d::framebuffer::Framebuffer;
+use rust_maze3d::item::extract_items;
+use rust_maze3d::maze::load_maze;
+use rust_maze3d::player::Player;
+use rust_maze3d::render::{render_view, RenderOptions, Scene};
+use rust_maze3d::teleport::extract_teleporters;
+use rust_maze3d::texture::{Filter, Texture, TextureRegistry};
+
+const BLOCK_SIZE: usize = 100;
+const RESOLUTIONS: [(usize, usize); 3] = [(640, 360), (1200, 720), (1920, 1080)];
+
+// Un cuadro completo de techo, paredes y piso desde el pasillo de las cintas del nivel,
+// repartido entre distintas cantidades de hilos
+fn column_rendering(c: &mut Criterion) {
+    let mut maze = load_maze("maze.txt").expect("maze.txt");
+    extract_items(&mut maze);
+    let floor = extract_floor(&mut maze);
+    extract_teleporters(&mut maze).expect("teleporters");
+    let doors = find_doors(&maze);
+    let textures = TextureRegistry::load("assets/textures.txt").expect("texture manifest");
+    let placeholder = Texture::placeholder();
+    let scene = Scene {
+        maze: &maze,
+        floor: &floor,
+        doors: &doors,
+        textures: &textures,
+        placeholder: &placeholder,
+        block_size: BLOCK_SIZE,
+    };
+    let player = Player::new(Vec2::new(150.0, 150.0), 0.0, std::f32::consts::PI / 3.0);
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut thread_counts = vec![1, 2, 4, max_threads];
+    thread_counts.sort();
+    thread_counts.dedup();
+
+    let mut group = c.benchmark_group("column_rendering");
+    for (width, height) in RESOLUTIONS {
+        let mut framebuffer = Framebuffer::new(width, height);
+        let mut z_buffer = vec![f32::INFINITY; width * height];
+        for &threads in &thread_counts {
+            let options =
+                RenderOptions { filter: Filter::Nearest, view_distance: f32::INFINITY, threads };
+            let resolution = format!("{}x{}", width, height);
+            let id = BenchmarkId::new(format!("{}_threads", threads), resolution);
+            group.bench_function(id, |b| {
+                b.iter(|| render_view(&mut framebuffer, &scene, &player, options, &mut z_buffer))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, column_rendering);
+criterion_main!(benches);
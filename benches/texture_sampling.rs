@@ -0,0 +1,69 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_maze3d::texture::{Filter, Texture};
+
+const WIDTH: usize = 1200;
+const HEIGHT: usize = 720;
+
+// Alto de cada columna en pantalla, como si el jugador mirara un pasillo en diagonal
+fn column_heights() -> Vec<usize> {
+    (0..WIDTH).map(|i| 120 + (i * 600) / WIDTH).collect()
+}
+
+// El formato anterior: un Vec por columna, con doble indirección por texel
+fn nested_texels(texture: &Texture) -> Vec<Vec<u32>> {
+    (0..texture.width)
+        .map(|x| {
+            let u = (x as f32 + 0.5) / texture.width as f32;
+            (0..texture.height)
+                .map(|y| {
+                    let v = (y as f32 + 0.5) / texture.height as f32;
+                    texture.sample(u, v, 0, Filter::Nearest)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn frame_nested(texels: &[Vec<u32>], heights: &[usize], buffer: &mut [u32]) {
+    let (width, height) = (texels.len() as f32, texels[0].len() as f32);
+    for (i, &column_height) in heights.iter().enumerate() {
+        let tx = (i as f32 / WIDTH as f32 * width) as usize;
+        let top = (HEIGHT - column_height) / 2;
+        for y in top..top + column_height {
+            let ty = ((y - top) as f32 / column_height as f32 * height) as usize;
+            buffer[y * WIDTH + i] = texels[tx][ty];
+        }
+    }
+}
+
+fn frame_strips(texture: &Texture, filter: Filter, heights: &[usize], buffer: &mut [u32]) {
+    for (i, &column_height) in heights.iter().enumerate() {
+        let strip = texture.strip(i as f32 / WIDTH as f32, 0, filter);
+        let top = (HEIGHT - column_height) / 2;
+        strip.sample_run(0.0, 1.0 / column_height as f32, column_height, |k, color| {
+            buffer[(top + k) * WIDTH + i] = color;
+        });
+    }
+}
+
+fn bench_wall_frame(c: &mut Criterion) {
+    let texture = Texture::load("assets/wall.jpg").expect("wall texture");
+    let nested = nested_texels(&texture);
+    let heights = column_heights();
+    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+
+    let mut group = c.benchmark_group("wall_frame_1200x720");
+    group.bench_function("nested_vec", |b| {
+        b.iter(|| frame_nested(black_box(&nested), &heights, &mut buffer))
+    });
+    group.bench_function("flat_strip_nearest", |b| {
+        b.iter(|| frame_strips(black_box(&texture), Filter::Nearest, &heights, &mut buffer))
+    });
+    group.bench_function("flat_strip_bilinear", |b| {
+        b.iter(|| frame_strips(black_box(&texture), Filter::Bilinear, &heights, &mut buffer))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_wall_frame);
+criterion_main!(benches);
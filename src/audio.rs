@@ -1,11 +1,13 @@
 use rodio::{Decoder, OutputStream, Sink, Source};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor, Read};
 use std::sync::{Arc, Mutex};
 
+// Sin salida de audio (por ejemplo en un servidor sin tarjeta de sonido) el reproductor
+// queda mudo y sus métodos no hacen nada
 pub struct AudioPlayer {
-    sink: Arc<Mutex<Sink>>,
-    _stream: OutputStream,
+    sink: Option<Arc<Mutex<Sink>>>,
+    _stream: Option<OutputStream>,
 }
 
 impl AudioPlayer {
@@ -20,13 +22,20 @@ impl AudioPlayer {
         sink.pause();
 
         Ok(Self {
-            sink: Arc::new(Mutex::new(sink)),
-            _stream: stream,
+            sink: Some(Arc::new(Mutex::new(sink))),
+            _stream: Some(stream),
         })
     }
 
+    pub fn muted() -> Self {
+        Self { sink: None, _stream: None }
+    }
+
     pub fn play(&self) {
-        if let Ok(sink) = self.sink.lock() {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        if let Ok(sink) = sink.lock() {
             sink.play();
         } else {
             eprintln!("Failed to lock the sink for playback.");
@@ -34,7 +43,10 @@ impl AudioPlayer {
     }
 
     pub fn pause(&self) {
-        if let Ok(sink) = self.sink.lock() {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        if let Ok(sink) = sink.lock() {
             sink.pause();
         } else {
             eprintln!("Failed to lock the sink to pause playback.");
@@ -42,11 +54,60 @@ impl AudioPlayer {
     }
 
     pub fn is_playing(&self) -> bool {
-        if let Ok(sink) = self.sink.lock() {
+        let Some(sink) = &self.sink else {
+            return false;
+        };
+        if let Ok(sink) = sink.lock() {
             !sink.is_paused()
         } else {
             eprintln!("Failed to lock the sink to check if it's playing.");
             false
         }
     }
-}
\ No newline at end of file
+}
+
+pub struct SoundEffect {
+    sink: Option<Arc<Mutex<Sink>>>,
+    data: Arc<[u8]>,
+    _stream: Option<OutputStream>,
+}
+
+impl SoundEffect {
+    pub fn new(sound_file: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (stream, stream_handle) = OutputStream::try_default()?;
+        let sink = Sink::try_new(&stream_handle)?;
+        sink.set_volume(0.5);
+
+        let mut data = Vec::new();
+        File::open(sound_file)?.read_to_end(&mut data)?;
+        // Validamos el formato al cargar y no en el primer uso
+        Decoder::new(Cursor::new(data.clone()))?;
+
+        Ok(Self {
+            sink: Some(Arc::new(Mutex::new(sink))),
+            data: data.into(),
+            _stream: Some(stream),
+        })
+    }
+
+    pub fn muted() -> Self {
+        Self { sink: None, data: Arc::from([]), _stream: None }
+    }
+
+    pub fn play(&self) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        if let Ok(sink) = sink.lock() {
+            match Decoder::new(Cursor::new(self.data.clone())) {
+                Ok(source) => {
+                    sink.append(source);
+                    sink.play();
+                }
+                Err(e) => eprintln!("Failed to decode sound effect: {}", e),
+            }
+        } else {
+            eprintln!("Failed to lock the sink to play the sound effect.");
+        }
+    }
+}
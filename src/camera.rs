@@ -0,0 +1,142 @@
+use nalgebra_glm::Vec2;
+use crate::player::Player;
+
+// Alturas del mundo, en bloques. Los ojos quedan a media pared, así que el horizonte
+// pasa por el centro de la pantalla.
+pub const WALL_HEIGHT: f32 = 0.5;
+pub const EYE_HEIGHT: f32 = WALL_HEIGHT / 2.0;
+// Más cerca que esto (en bloques) un sprite se descarta para no llenar la pantalla
+const NEAR_PLANE: f32 = 0.1;
+
+// Cámara de proyección en perspectiva: el plano de proyección queda a la distancia en la
+// que `fov` abarca exactamente el ancho de la pantalla. Paredes, piso y sprites proyectan
+// con la misma distancia, así que encajan entre sí en cualquier columna.
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub pos: Vec2,
+    pub angle: f32,
+    pub width: usize,
+    pub height: usize,
+    pub block_size: usize,
+    // Distancia en pixeles del ojo al plano de proyección
+    pub projection: f32,
+}
+
+impl Camera {
+    pub fn new(player: &Player, width: usize, height: usize, block_size: usize) -> Self {
+        Self {
+            pos: player.pos,
+            angle: player.a,
+            width,
+            height,
+            block_size,
+            projection: (width as f32 / 2.0) / (player.fov / 2.0).tan(),
+        }
+    }
+
+    pub fn horizon(&self) -> f32 {
+        self.height as f32 / 2.0
+    }
+
+    // Ángulo del rayo que pasa por el centro de la columna `column`
+    pub fn ray_angle(&self, column: usize) -> f32 {
+        let offset = column as f32 + 0.5 - self.width as f32 / 2.0;
+        self.angle + (offset / self.projection).atan()
+    }
+
+    // Alto en pantalla de algo de `size` bloques a la distancia perpendicular `depth`
+    pub fn projected_size(&self, size: f32, depth: f32) -> f32 {
+        size * self.block_size as f32 * self.projection / depth
+    }
+
+    // Fila de pantalla donde se ve algo a `height` bloques del piso y a `depth` de distancia
+    pub fn screen_y(&self, height: f32, depth: f32) -> f32 {
+        self.horizon() + self.projected_size(EYE_HEIGHT - height, depth)
+    }
+
+    // Distancia perpendicular al punto del piso (o del techo) que se ve en la fila `y`
+    pub fn row_distance(&self, y: usize) -> f32 {
+        let eye = EYE_HEIGHT * self.block_size as f32;
+        eye * self.projection / (y as f32 + 0.5 - self.horizon()).abs()
+    }
+
+    // Columna de pantalla (sin redondear) y distancia perpendicular de un punto del
+    // mundo, o None si queda detrás de la cámara
+    pub fn project(&self, point: Vec2) -> Option<(f32, f32)> {
+        let (sin_a, cos_a) = self.angle.sin_cos();
+        let relative = point - self.pos;
+        let depth = relative.x * cos_a + relative.y * sin_a;
+        if depth < NEAR_PLANE * self.block_size as f32 {
+            return None;
+        }
+        let lateral = relative.y * cos_a - relative.x * sin_a;
+        Some((self.width as f32 / 2.0 + lateral * self.projection / depth, depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cast_function::cast_ray_from;
+
+    const BLOCK: usize = 100;
+
+    fn camera(angle: f32) -> Camera {
+        let player = Player::new(Vec2::new(250.0, 350.0), angle, std::f32::consts::PI / 3.0);
+        Camera::new(&player, 640, 360, BLOCK)
+    }
+
+    #[test]
+    fn wall_hits_project_back_onto_their_column() {
+        let maze: Vec<Vec<char>> = ["------", "-    -", "- -  -", "-   g-", "-    -", "------"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+
+        for angle in [0.0, 0.7, 2.5, -1.9] {
+            let camera = camera(angle);
+            for column in 0..camera.width {
+                let a = camera.ray_angle(column);
+                let hit = cast_ray_from(&maze, &[], camera.pos, a, BLOCK);
+                let point = camera.pos + Vec2::new(a.cos(), a.sin()) * hit.distance;
+
+                let (screen_x, depth) =
+                    camera.project(point).expect("wall hit behind the camera");
+                let error = (screen_x - (column as f32 + 0.5)).abs();
+                assert!(error < 0.5, "column {column} at angle {angle} projected to {screen_x}");
+                // La profundidad del sprite es la misma que usa la pared en el z-buffer
+                let wall_depth = hit.distance * (a - camera.angle).cos();
+                assert!((depth - wall_depth).abs() < 1e-2 * wall_depth.max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn sprites_stand_on_the_wall_bottom_and_the_floor_row() {
+        let camera = camera(0.0);
+
+        for depth in [60.0, 150.0, 400.0, 1200.0] {
+            let sprite_bottom = camera.screen_y(0.0, depth);
+
+            // La pared de la misma distancia queda centrada en el horizonte
+            let wall = camera.projected_size(WALL_HEIGHT, depth);
+            assert!((sprite_bottom - (camera.horizon() + wall / 2.0)).abs() < 1e-3);
+            let wall_top = camera.screen_y(WALL_HEIGHT, depth);
+            assert!((wall_top - (camera.horizon() - wall / 2.0)).abs() < 1e-3);
+
+            // La fila del piso que está a esa distancia es la que contiene la base
+            if sprite_bottom < camera.height as f32 {
+                let row = (camera.height / 2..camera.height)
+                    .min_by(|&a, &b| {
+                        let error = |y| (camera.row_distance(y) - depth).abs();
+                        error(a).total_cmp(&error(b))
+                    })
+                    .unwrap();
+                assert!(
+                    (row as f32..=row as f32 + 1.0).contains(&sprite_bottom),
+                    "depth {depth}: bottom {sprite_bottom} is not in floor row {row}"
+                );
+            }
+        }
+    }
+}
@@ -1,24 +1,111 @@
+use nalgebra_glm::Vec2;
+use crate::door::{door_at, is_door, Door};
 use crate::framebuffer::Framebuffer;
 use crate::player::Player;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Face {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "north" => Some(Face::North),
+            "south" => Some(Face::South),
+            "east" => Some(Face::East),
+            "west" => Some(Face::West),
+            _ => None,
+        }
+    }
+}
+
 pub struct Intersect {
     pub distance: f32,
     pub impact: char,
-    pub tx: usize,
+    pub face: Face,
+    // Coordenada horizontal de la textura, normalizada en [0, 1)
+    pub tx: f32,
 }
 
 pub fn cast_ray(
     framebuffer: &mut Framebuffer,
     maze: &[Vec<char>],
+    doors: &[Door],
     player: &Player,
     a: f32,
     block_size: usize,
     draw_line: bool,
 ) -> Intersect {
-    let mut d = 0.0;
-
     framebuffer.set_current_color(0xFFFFFF);
 
+    march_ray(maze, doors, player.pos, a, block_size, |x, y| {
+        if draw_line {
+            framebuffer.point(x, y);
+        }
+    })
+}
+
+// Lo mismo que `cast_ray` pero desde cualquier punto y sin dibujar; lo usan, por ejemplo,
+// los enemigos para saber si ven al jugador.
+pub fn cast_ray_from(
+    maze: &[Vec<char>],
+    doors: &[Door],
+    origin: Vec2,
+    a: f32,
+    block_size: usize,
+) -> Intersect {
+    march_ray(maze, doors, origin, a, block_size, |_, _| {})
+}
+
+// Entrega a `on_hit` cada pared que cruza el rayo, de la más cercana a la más lejana,
+// mientras devuelva true. Sirve para ver por encima de las paredes bajas.
+pub fn cast_ray_layers(
+    maze: &[Vec<char>],
+    doors: &[Door],
+    origin: Vec2,
+    a: f32,
+    block_size: usize,
+    on_hit: impl FnMut(Intersect) -> bool,
+) {
+    march(maze, doors, origin, a, block_size, |_, _| {}, on_hit);
+}
+
+fn march_ray(
+    maze: &[Vec<char>],
+    doors: &[Door],
+    origin: Vec2,
+    a: f32,
+    block_size: usize,
+    visit: impl FnMut(usize, usize),
+) -> Intersect {
+    let mut first = None;
+    let distance = march(maze, doors, origin, a, block_size, visit, |hit| {
+        first = Some(hit);
+        false
+    });
+    // Si el mapa no está cerrado el rayo puede salirse sin chocar con nada
+    first.unwrap_or(Intersect { distance, impact: ' ', face: Face::North, tx: 0.0 })
+}
+
+// Avanza el rayo hasta que `on_hit` pida parar o salga del mapa, y devuelve la distancia
+// recorrida
+fn march(
+    maze: &[Vec<char>],
+    doors: &[Door],
+    origin: Vec2,
+    a: f32,
+    block_size: usize,
+    mut visit: impl FnMut(usize, usize),
+    mut on_hit: impl FnMut(Intersect) -> bool,
+) -> f32 {
+    let mut d = 0.0;
+    // Cada celda se informa una sola vez aunque el rayo siga dentro de ella varios pasos
+    let mut last_cell = None;
+
     let cos_a = a.cos();
     let sin_a = a.sin();
 
@@ -26,8 +113,10 @@ pub fn cast_ray(
         let cos_d = d * cos_a;
         let sin_d = d * sin_a;
 
-        let x = (player.pos.x + cos_d) as usize;
-        let y = (player.pos.y + sin_d) as usize;
+        let fx = origin.x + cos_d;
+        let fy = origin.y + sin_d;
+        let x = fx as usize;
+        let y = fy as usize;
 
         let i = x / block_size;
         let j = y / block_size;
@@ -35,26 +124,92 @@ pub fn cast_ray(
         let hitx = x % block_size;
         let hity = y % block_size;
 
-        let maxhit = if hitx > 1 && hitx < block_size - 1 {
-            hitx
+        // Si el punto no está pegado a un borde vertical, el rayo chocó con la cara
+        // superior o inferior de la celda
+        let (maxhit, face) = if hitx > 1 && hitx < block_size - 1 {
+            (fx, if hity < block_size / 2 { Face::North } else { Face::South })
         } else {
-            hity
+            (fy, if hitx < block_size / 2 { Face::West } else { Face::East })
         };
 
-        if draw_line {
-            framebuffer.point(x, y);
+        if fx < 0.0 || fy < 0.0 {
+            return d;
         }
+        let Some(&impact) = maze.get(j).and_then(|row| row.get(i)) else {
+            return d;
+        };
+
+        visit(x, y);
 
-        if let Some(&impact) = maze.get(j).and_then(|row| row.get(i)) {
-            if impact != ' ' {
-                return Intersect {
+        if last_cell != Some((j, i)) {
+            let hit = if is_door(impact) {
+                door_at(doors, j, i)
+                    .and_then(|door| hit_door_panel(door, impact, origin, cos_a, sin_a, block_size))
+            } else if impact != ' ' {
+                Some(Intersect {
                     distance: d,
                     impact,
-                    tx: maxhit * 128 / block_size,
-                };
+                    face,
+                    tx: (maxhit / block_size as f32).fract(),
+                })
+            } else {
+                None
+            };
+            if let Some(hit) = hit {
+                last_cell = Some((j, i));
+                if !on_hit(hit) {
+                    return d;
+                }
             }
         }
 
         d += 2.0;
     }
 }
+
+// El panel de la puerta está hundido a mitad de la celda y se desliza hacia un lado
+// conforme se abre; el rayo lo atraviesa por la parte ya descubierta.
+fn hit_door_panel(
+    door: &Door,
+    impact: char,
+    origin: Vec2,
+    cos_a: f32,
+    sin_a: f32,
+    block_size: usize,
+) -> Option<Intersect> {
+    let block = block_size as f32;
+    let (origin, dir, along_origin, along_dir, cell_start) = if door.horizontal {
+        let mid = (door.row as f32 + 0.5) * block;
+        (origin.y - mid, sin_a, origin.x, cos_a, door.col as f32 * block)
+    } else {
+        let mid = (door.col as f32 + 0.5) * block;
+        (origin.x - mid, cos_a, origin.y, sin_a, door.row as f32 * block)
+    };
+    let face = match (door.horizontal, dir > 0.0) {
+        (true, true) => Face::North,
+        (true, false) => Face::South,
+        (false, true) => Face::West,
+        (false, false) => Face::East,
+    };
+
+    if dir.abs() < f32::EPSILON {
+        return None;
+    }
+    let distance = -origin / dir;
+    if distance < 0.0 {
+        return None;
+    }
+
+    let offset = along_origin + distance * along_dir - cell_start;
+    let slide = door.open * block;
+    if offset < slide || offset >= block {
+        return None;
+    }
+
+    Some(Intersect {
+        distance,
+        impact,
+        face,
+        tx: (offset - slide) / block,
+    })
+}
@@ -0,0 +1,173 @@
+use nalgebra_glm::Vec2;
+use crate::audio::SoundEffect;
+use crate::item::KeyColor;
+use crate::player::Player;
+
+const DOOR_SPEED: f32 = 0.04;
+const DOOR_HOLD_FRAMES: u32 = 180;
+const DOOR_PROXIMITY: f32 = 0.8;
+const DOOR_REACH: f32 = 1.5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum DoorState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+pub struct Door {
+    pub row: usize,
+    pub col: usize,
+    // El panel corre a lo largo del eje x cuando hay muros a la izquierda y derecha
+    pub horizontal: bool,
+    // 0.0 cerrada, 1.0 completamente abierta
+    pub open: f32,
+    // Color de la llave necesaria para abrirla, si está cerrada con llave
+    pub lock: Option<KeyColor>,
+    state: DoorState,
+    timer: u32,
+    moving: bool,
+}
+
+impl Door {
+    fn new(row: usize, col: usize, horizontal: bool, lock: Option<KeyColor>) -> Self {
+        Self {
+            row,
+            col,
+            horizontal,
+            open: 0.0,
+            lock,
+            state: DoorState::Closed,
+            timer: 0,
+            moving: false,
+        }
+    }
+
+    pub fn is_passable(&self) -> bool {
+        self.open >= 1.0
+    }
+
+    pub fn can_open(&self, player: &Player) -> bool {
+        self.lock.map_or(true, |color| player.has_key(color))
+    }
+
+    fn center(&self, block_size: usize) -> Vec2 {
+        Vec2::new(
+            (self.col as f32 + 0.5) * block_size as f32,
+            (self.row as f32 + 0.5) * block_size as f32,
+        )
+    }
+
+    fn contains(&self, pos: Vec2, block_size: usize) -> bool {
+        (pos.x / block_size as f32).floor() as usize == self.col
+            && (pos.y / block_size as f32).floor() as usize == self.row
+    }
+
+    fn trigger(&mut self) {
+        match self.state {
+            DoorState::Closed | DoorState::Closing => self.state = DoorState::Opening,
+            DoorState::Open => self.timer = DOOR_HOLD_FRAMES,
+            DoorState::Opening => {}
+        }
+    }
+}
+
+pub fn is_door(cell: char) -> bool {
+    cell == 'D' || KeyColor::from_door_glyph(cell).is_some()
+}
+
+pub fn find_doors(maze: &[Vec<char>]) -> Vec<Door> {
+    let is_solid = |row: usize, col: Option<usize>| {
+        col.and_then(|c| maze.get(row).and_then(|r| r.get(c)))
+            .is_some_and(|&cell| cell != ' ' && !is_door(cell))
+    };
+
+    let mut doors = Vec::new();
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            if is_door(cell) {
+                let horizontal = is_solid(row, col.checked_sub(1)) && is_solid(row, Some(col + 1));
+                doors.push(Door::new(row, col, horizontal, KeyColor::from_door_glyph(cell)));
+            }
+        }
+    }
+    doors
+}
+
+pub fn door_at(doors: &[Door], row: usize, col: usize) -> Option<&Door> {
+    doors.iter().find(|door| door.row == row && door.col == col)
+}
+
+// Piso libre o una puerta ya abierta del todo
+pub fn is_open_cell(maze: &[Vec<char>], doors: &[Door], row: usize, col: usize) -> bool {
+    maze.get(row).and_then(|r| r.get(col)).is_some_and(|&cell| {
+        cell == ' ' || (is_door(cell) && door_at(doors, row, col).is_some_and(Door::is_passable))
+    })
+}
+
+// Abre la puerta que el jugador tiene enfrente, si está a su alcance
+pub fn use_door(doors: &mut [Door], player: &Player, block_size: usize) {
+    let reach = block_size as f32 * DOOR_REACH;
+    let dir = Vec2::new(player.a.cos(), player.a.sin());
+
+    let mut d = 0.0;
+    while d <= reach {
+        let probe = player.pos + dir * d;
+        if let Some(door) = doors.iter_mut().find(|door| door.contains(probe, block_size)) {
+            if door.can_open(player) {
+                door.trigger();
+            }
+            return;
+        }
+        d += block_size as f32 / 10.0;
+    }
+}
+
+pub fn update_doors(doors: &mut [Door], player: &Player, block_size: usize, sound: &SoundEffect) {
+    for door in doors.iter_mut() {
+        let occupied = door.contains(player.pos, block_size);
+        let near =
+            (player.pos - door.center(block_size)).norm() < block_size as f32 * DOOR_PROXIMITY;
+
+        if near && door.state == DoorState::Closed && door.can_open(player) {
+            door.trigger();
+        }
+
+        match door.state {
+            DoorState::Closed => {}
+            DoorState::Opening => {
+                door.open = (door.open + DOOR_SPEED).min(1.0);
+                if door.open >= 1.0 {
+                    door.state = DoorState::Open;
+                    door.timer = DOOR_HOLD_FRAMES;
+                }
+            }
+            DoorState::Open => {
+                if occupied || near {
+                    door.timer = DOOR_HOLD_FRAMES;
+                } else if door.timer > 0 {
+                    door.timer -= 1;
+                } else {
+                    door.state = DoorState::Closing;
+                }
+            }
+            DoorState::Closing => {
+                if occupied {
+                    door.state = DoorState::Opening;
+                } else {
+                    door.open = (door.open - DOOR_SPEED).max(0.0);
+                    if door.open <= 0.0 {
+                        door.state = DoorState::Closed;
+                    }
+                }
+            }
+        }
+
+        let moving = matches!(door.state, DoorState::Opening | DoorState::Closing);
+        if moving && !door.moving {
+            sound.play();
+        }
+        door.moving = moving;
+    }
+}
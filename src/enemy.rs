@@ -0,0 +1,282 @@
+use nalgebra_glm::Vec2;
+use crate::cast_function::cast_ray_from;
+use crate::door::{is_open_cell, Door};
+use crate::entity::Entity;
+use crate::player::Player;
+use crate::solver::find_path;
+
+// Ángulo total del cono de visión mientras patrullan; persiguiendo no lo pierden de vista
+// aunque quede a su espalda
+const VIEW_CONE: f32 = 2.0 * std::f32::consts::FRAC_PI_3;
+const ALERT_SECONDS: f32 = 0.6;
+const LOSE_SIGHT_SECONDS: f32 = 3.0;
+const REPATH_SECONDS: f32 = 0.5;
+// Radianes por segundo
+const TURN_SPEED: f32 = 6.0;
+const IDLE_TURN_SPEED: f32 = 0.4;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "FACIL",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "DIFICIL",
+        }
+    }
+
+    // Bloques por segundo
+    pub fn speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.2,
+            Difficulty::Normal => 1.8,
+            Difficulty::Hard => 2.4,
+        }
+    }
+
+    // Bloques hasta donde alcanzan a ver al jugador
+    pub fn vision(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 3.0,
+            Difficulty::Normal => 5.0,
+            Difficulty::Hard => 8.0,
+        }
+    }
+
+    // En difícil que te atrapen termina la partida; en los demás se vuelve al inicio
+    pub fn caught_ends_run(&self) -> bool {
+        *self == Difficulty::Hard
+    }
+
+    // Vida que quita cada vez que un enemigo alcanza al jugador
+    pub fn enemy_damage(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 20,
+            Difficulty::Normal => 34,
+            Difficulty::Hard => 50,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EnemyState {
+    Patrol,
+    // Acaba de ver al jugador y se queda quieto un momento antes de perseguirlo
+    Alert,
+    Chase,
+}
+
+pub struct Enemy {
+    pub state: EnemyState,
+    // Celdas (fila, columna) que recorre en orden mientras patrulla
+    route: Vec<(usize, usize)>,
+    waypoint: usize,
+    // Celdas pendientes hacia el destino actual, la siguiente al final
+    path: Vec<(usize, usize)>,
+    repath: f32,
+    // Tiempo restante de alerta, o tiempo sin ver al jugador durante la persecución
+    timer: f32,
+}
+
+impl Enemy {
+    pub fn new(route: Vec<(usize, usize)>) -> Self {
+        Self {
+            state: EnemyState::Patrol,
+            route,
+            waypoint: 0,
+            path: Vec::new(),
+            repath: 0.0,
+            timer: 0.0,
+        }
+    }
+
+    // Vuelve a patrullar desde donde esté, por ejemplo después de atrapar al jugador
+    pub fn reset(&mut self) {
+        self.state = EnemyState::Patrol;
+        self.path.clear();
+    }
+}
+
+fn cell_of(pos: Vec2, block_size: usize) -> (usize, usize) {
+    ((pos.y / block_size as f32) as usize, (pos.x / block_size as f32) as usize)
+}
+
+fn cell_center((row, col): (usize, usize), block_size: usize) -> Vec2 {
+    Vec2::new(
+        (col as f32 + 0.5) * block_size as f32,
+        (row as f32 + 0.5) * block_size as f32,
+    )
+}
+
+fn wrap_angle(a: f32) -> f32 {
+    (a + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
+fn turn_towards(facing: f32, target: f32, max_turn: f32) -> f32 {
+    facing + wrap_angle(target - facing).clamp(-max_turn, max_turn)
+}
+
+fn in_view_cone(pos: Vec2, facing: f32, player: &Player) -> bool {
+    let to_player = player.pos - pos;
+    wrap_angle(to_player.y.atan2(to_player.x) - facing).abs() <= VIEW_CONE / 2.0
+}
+
+// Lo ve si está a su alcance y ninguna pared se interpone
+fn can_see(
+    pos: Vec2,
+    player: &Player,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    range: f32,
+    block_size: usize,
+) -> bool {
+    let to_player = player.pos - pos;
+    let distance = to_player.norm();
+    if distance > range {
+        return false;
+    }
+
+    let angle = to_player.y.atan2(to_player.x);
+    cast_ray_from(maze, doors, pos, angle, block_size).distance >= distance
+}
+
+// Avanza hacia la siguiente celda del camino y devuelve hacia dónde se movió, o None si
+// no hay por dónde seguir
+fn follow_path(
+    enemy: &mut Enemy,
+    pos: &mut Vec2,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    step: f32,
+    block_size: usize,
+) -> Option<f32> {
+    let &next = enemy.path.last()?;
+    if !is_open_cell(maze, doors, next.0, next.1) {
+        enemy.path.clear();
+        return None;
+    }
+
+    let to_next = cell_center(next, block_size) - *pos;
+    let distance = to_next.norm();
+    if distance <= step {
+        *pos = cell_center(next, block_size);
+        enemy.path.pop();
+    } else {
+        *pos += to_next * (step / distance);
+    }
+    Some(to_next.y.atan2(to_next.x))
+}
+
+fn plan(
+    from: (usize, usize),
+    to: (usize, usize),
+    maze: &[Vec<char>],
+    doors: &[Door],
+) -> Vec<(usize, usize)> {
+    let mut path = find_path(maze, from, to, |row, col| is_open_cell(maze, doors, row, col))
+        .unwrap_or_default();
+    path.reverse();
+    path
+}
+
+// Actualiza un enemigo durante un tick. Devuelve true si atrapó al jugador.
+pub fn update_enemy(
+    entity: &mut Entity,
+    player: &Player,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    difficulty: Difficulty,
+    block_size: usize,
+    dt: f32,
+) -> bool {
+    let caught = entity.touches(player.pos);
+    let Entity { pos, facing, animator, enemy, .. } = entity;
+    let Some(enemy) = enemy else {
+        return false;
+    };
+
+    let block = block_size as f32;
+    let range = difficulty.vision() * block;
+    let step = difficulty.speed() * block * dt;
+    let max_turn = TURN_SPEED * dt;
+    let here = cell_of(*pos, block_size);
+
+    match enemy.state {
+        EnemyState::Patrol => {
+            if in_view_cone(*pos, *facing, player)
+                && can_see(*pos, player, maze, doors, range, block_size)
+            {
+                enemy.state = EnemyState::Alert;
+                enemy.timer = ALERT_SECONDS;
+            } else if enemy.route.is_empty() {
+                *facing += IDLE_TURN_SPEED * dt;
+            } else {
+                if enemy.path.is_empty() {
+                    if here == enemy.route[enemy.waypoint] {
+                        enemy.waypoint = (enemy.waypoint + 1) % enemy.route.len();
+                    }
+                    enemy.path = plan(here, enemy.route[enemy.waypoint], maze, doors);
+                }
+                if let Some(heading) = follow_path(enemy, pos, maze, doors, step, block_size) {
+                    *facing = turn_towards(*facing, heading, max_turn);
+                }
+            }
+        }
+        EnemyState::Alert => {
+            let to_player = player.pos - *pos;
+            *facing = turn_towards(*facing, to_player.y.atan2(to_player.x), max_turn);
+            enemy.timer -= dt;
+            if enemy.timer <= 0.0 {
+                enemy.state = EnemyState::Chase;
+                enemy.timer = 0.0;
+                enemy.repath = 0.0;
+            }
+        }
+        EnemyState::Chase => {
+            if can_see(*pos, player, maze, doors, range, block_size) {
+                enemy.timer = 0.0;
+            } else {
+                enemy.timer += dt;
+            }
+
+            if enemy.timer > LOSE_SIGHT_SECONDS {
+                enemy.reset();
+            } else {
+                enemy.repath -= dt;
+                if enemy.repath <= 0.0 || enemy.path.is_empty() {
+                    enemy.path = plan(here, cell_of(player.pos, block_size), maze, doors);
+                    enemy.repath = REPATH_SECONDS;
+                }
+                if let Some(heading) = follow_path(enemy, pos, maze, doors, step, block_size) {
+                    *facing = turn_towards(*facing, heading, max_turn);
+                } else if here == cell_of(player.pos, block_size) {
+                    // En la misma celda ya no hace falta camino: va directo al jugador
+                    let to_player = player.pos - *pos;
+                    let distance = to_player.norm();
+                    if distance > f32::EPSILON {
+                        *pos += to_player * (step.min(distance) / distance);
+                        *facing = turn_towards(*facing, to_player.y.atan2(to_player.x), max_turn);
+                    }
+                }
+            }
+        }
+    }
+
+    let animation = match enemy.state {
+        EnemyState::Alert => "react",
+        _ if enemy.path.is_empty() => "idle",
+        _ => "walk",
+    };
+    animator.play(animation);
+
+    if caught {
+        enemy.reset();
+    }
+    caught
+}
@@ -0,0 +1,217 @@
+use nalgebra_glm::Vec2;
+use crate::door::Door;
+use crate::enemy::{update_enemy, Difficulty, Enemy};
+use crate::item::{Item, ItemKind};
+use crate::player::Player;
+use crate::sprite::Animator;
+
+// Radios de colisión, en bloques
+const PICKUP_RADIUS: f32 = 0.4;
+const ALIEN_RADIUS: f32 = 0.4;
+
+// Qué dibujar para la entidad; el juego decide qué textura u hoja corresponde a cada uno
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EntitySprite {
+    Alien,
+    Item(ItemKind),
+}
+
+pub struct Pickup {
+    pub kind: ItemKind,
+}
+
+pub struct Trigger {
+    pub message: String,
+    pub fired: bool,
+}
+
+pub struct Entity {
+    pub pos: Vec2,
+    pub facing: f32,
+    pub radius: f32,
+    // Los disparadores no se dibujan: solo reaccionan cuando el jugador los pisa
+    pub sprite: Option<EntitySprite>,
+    pub animator: Animator,
+    pub pickup: Option<Pickup>,
+    pub enemy: Option<Enemy>,
+    pub trigger: Option<Trigger>,
+    pub removed: bool,
+}
+
+impl Entity {
+    fn new(pos: Vec2, radius: f32, sprite: Option<EntitySprite>) -> Self {
+        Self {
+            pos,
+            facing: 0.0,
+            radius,
+            sprite,
+            animator: Animator::new("idle"),
+            pickup: None,
+            enemy: None,
+            trigger: None,
+            removed: false,
+        }
+    }
+
+    pub fn item(item: &Item, block_size: usize) -> Self {
+        let block = block_size as f32;
+        let sprite = Some(EntitySprite::Item(item.kind));
+        let mut entity = Self::new(item.pos(block_size), PICKUP_RADIUS * block, sprite);
+        entity.pickup = Some(Pickup { kind: item.kind });
+        entity
+    }
+
+    pub fn alien(pos: Vec2, facing: f32, route: Vec<(usize, usize)>, block_size: usize) -> Self {
+        let block = block_size as f32;
+        let mut entity = Self::new(pos, ALIEN_RADIUS * block, Some(EntitySprite::Alien));
+        entity.facing = facing;
+        entity.enemy = Some(Enemy::new(route));
+        entity
+    }
+
+    pub fn touches(&self, pos: Vec2) -> bool {
+        (self.pos - pos).norm() < self.radius
+    }
+
+    // Ángulo hacia la cámara relativo a donde mira la entidad, para elegir su vista
+    pub fn view_angle(&self, player: &Player) -> f32 {
+        (player.pos.y - self.pos.y).atan2(player.pos.x - self.pos.x) - self.facing
+    }
+}
+
+// Los puntos de patrulla se escriben como "columna,fila", igual que x e y
+fn parse_waypoint(text: &str) -> Option<(usize, usize)> {
+    let (col, row) = text.split_once(',')?;
+    Some((row.parse().ok()?, col.parse().ok()?))
+}
+
+// Lo que pasó en el mundo durante un tick y que el juego tiene que mostrar
+pub enum WorldEvent {
+    PickedUp(ItemKind),
+    Message(String),
+    // Un enemigo alcanzó al jugador; si no era invulnerable, además le quitó vida
+    Caught { damaged: bool },
+}
+
+#[derive(Default)]
+pub struct World {
+    pub entities: Vec<Entity>,
+}
+
+impl World {
+    // Las entidades se declaran en las directivas del nivel, con la posición en bloques:
+    //   alien <x> <y> <orientación en grados> [patrol <columna>,<fila> ...]
+    //   trigger <x> <y> <radio> <mensaje...>
+    // Las llaves siguen marcándose en la grilla y llegan aquí como objetos ya extraídos.
+    pub fn load(
+        directives: &[(usize, String)],
+        items: &[Item],
+        maze: &[Vec<char>],
+        block_size: usize,
+    ) -> Result<Self, String> {
+        let block = block_size as f32;
+        let mut entities: Vec<Entity> =
+            items.iter().map(|item| Entity::item(item, block_size)).collect();
+
+        for (line, directive) in directives {
+            let invalid = |message: &str| format!("line {}: {}", line, message);
+            let number = |field: Option<&str>, name: &str| {
+                field
+                    .and_then(|value| value.parse::<f32>().ok())
+                    .ok_or_else(|| invalid(&format!("invalid {}", name)))
+            };
+
+            let mut fields = directive.split_whitespace();
+            match fields.next() {
+                Some("alien") => {
+                    let x = number(fields.next(), "x")?;
+                    let y = number(fields.next(), "y")?;
+                    let facing = number(fields.next(), "facing")?;
+                    let mut route = Vec::new();
+                    match fields.next() {
+                        Some("patrol") => {
+                            for waypoint in fields {
+                                let (row, col) = parse_waypoint(waypoint).ok_or_else(|| {
+                                    invalid(&format!("invalid waypoint '{}'", waypoint))
+                                })?;
+                                if maze.get(row).and_then(|cells| cells.get(col)) != Some(&' ') {
+                                    let message = format!("waypoint '{}' is not floor", waypoint);
+                                    return Err(invalid(&message));
+                                }
+                                route.push((row, col));
+                            }
+                        }
+                        Some(other) => return Err(invalid(&format!("unexpected '{}'", other))),
+                        None => {}
+                    }
+                    let pos = Vec2::new(x * block, y * block);
+                    entities.push(Entity::alien(pos, facing.to_radians(), route, block_size));
+                }
+                Some("trigger") => {
+                    let x = number(fields.next(), "x")?;
+                    let y = number(fields.next(), "y")?;
+                    let radius = number(fields.next(), "radius")?;
+                    let message = fields.collect::<Vec<_>>().join(" ");
+                    if message.is_empty() {
+                        return Err(invalid("trigger needs a message"));
+                    }
+                    let pos = Vec2::new(x * block, y * block);
+                    let mut entity = Entity::new(pos, radius * block, None);
+                    entity.trigger = Some(Trigger { message, fired: false });
+                    entities.push(entity);
+                }
+                Some(kind) => return Err(invalid(&format!("unknown entity '{}'", kind))),
+                None => {}
+            }
+        }
+
+        Ok(Self { entities })
+    }
+
+    pub fn update(
+        &mut self,
+        player: &mut Player,
+        maze: &[Vec<char>],
+        doors: &[Door],
+        difficulty: Difficulty,
+        block_size: usize,
+        dt: f32,
+    ) -> Vec<WorldEvent> {
+        let mut events = Vec::new();
+
+        for entity in &mut self.entities {
+            let touching = entity.touches(player.pos);
+
+            if let Some(pickup) = &entity.pickup {
+                if touching {
+                    match pickup.kind {
+                        ItemKind::Key(color) => {
+                            if !player.has_key(color) {
+                                player.keys.push(color);
+                            }
+                        }
+                    }
+                    events.push(WorldEvent::PickedUp(pickup.kind));
+                    entity.removed = true;
+                }
+            }
+
+            if let Some(trigger) = &mut entity.trigger {
+                if !trigger.fired && touching {
+                    trigger.fired = true;
+                    events.push(WorldEvent::Message(trigger.message.clone()));
+                }
+            }
+
+            if update_enemy(entity, player, maze, doors, difficulty, block_size, dt) {
+                let damaged = player.damage(difficulty.enemy_damage());
+                events.push(WorldEvent::Caught { damaged });
+            }
+
+            entity.animator.update(dt);
+        }
+
+        self.entities.retain(|entity| !entity.removed);
+        events
+    }
+}
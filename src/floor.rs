@@ -0,0 +1,124 @@
+use nalgebra_glm::Vec2;
+use crate::player::Player;
+
+// Daño por cada golpe mientras se está parado sobre una celda peligrosa; la
+// invulnerabilidad del jugador marca cada cuánto se repite
+const HAZARD_DAMAGE: u32 = 10;
+// Fracción de la velocidad normal al caminar sobre una zona lenta
+const SLOW_FACTOR: f32 = 0.45;
+// Pixeles por cuadro que arrastra una cinta transportadora
+const CONVEYOR_SPEED: f32 = 2.5;
+// Bloques que se alcanzan a ver parado en una zona oscura
+pub const DARK_VIEW_DISTANCE: f32 = 2.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    East,
+    South,
+    West,
+    North,
+}
+
+impl Direction {
+    // Con la misma convención que `Player::a`: 0 hacia +x y creciendo hacia +y
+    pub fn angle(&self) -> f32 {
+        match self {
+            Direction::East => 0.0,
+            Direction::South => std::f32::consts::FRAC_PI_2,
+            Direction::West => std::f32::consts::PI,
+            Direction::North => -std::f32::consts::FRAC_PI_2,
+        }
+    }
+}
+
+// Tipo de piso de cada celda. Se marcan en el mapa con su propio carácter y, como los
+// objetos, la celda queda como pasillo después de leerlos.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FloorTile {
+    Normal,
+    Slow,
+    Conveyor(Direction),
+    Hazard,
+    Dark,
+}
+
+impl FloorTile {
+    pub fn from_glyph(cell: char) -> Option<Self> {
+        match cell {
+            ':' => Some(FloorTile::Slow),
+            '>' => Some(FloorTile::Conveyor(Direction::East)),
+            'v' => Some(FloorTile::Conveyor(Direction::South)),
+            '<' => Some(FloorTile::Conveyor(Direction::West)),
+            '^' => Some(FloorTile::Conveyor(Direction::North)),
+            '~' => Some(FloorTile::Hazard),
+            '.' => Some(FloorTile::Dark),
+            _ => None,
+        }
+    }
+
+    // El glifo también sirve para buscar la textura del piso en el manifiesto
+    pub fn glyph(&self) -> Option<char> {
+        match self {
+            FloorTile::Normal => None,
+            FloorTile::Slow => Some(':'),
+            FloorTile::Conveyor(Direction::East) => Some('>'),
+            FloorTile::Conveyor(Direction::South) => Some('v'),
+            FloorTile::Conveyor(Direction::West) => Some('<'),
+            FloorTile::Conveyor(Direction::North) => Some('^'),
+            FloorTile::Hazard => Some('~'),
+            FloorTile::Dark => Some('.'),
+        }
+    }
+
+    pub fn damage(&self) -> u32 {
+        match self {
+            FloorTile::Hazard => HAZARD_DAMAGE,
+            _ => 0,
+        }
+    }
+
+    pub fn speed_factor(&self) -> f32 {
+        match self {
+            FloorTile::Slow => SLOW_FACTOR,
+            _ => 1.0,
+        }
+    }
+
+    // Desplazamiento por cuadro que el piso le aplica al jugador aunque no camine
+    pub fn push(&self) -> Vec2 {
+        match self {
+            FloorTile::Conveyor(direction) => {
+                let a = direction.angle();
+                Vec2::new(a.cos(), a.sin()) * CONVEYOR_SPEED
+            }
+            _ => Vec2::new(0.0, 0.0),
+        }
+    }
+}
+
+pub fn extract_floor(maze: &mut [Vec<char>]) -> Vec<Vec<FloorTile>> {
+    maze.iter_mut()
+        .map(|cells| {
+            cells
+                .iter_mut()
+                .map(|cell| match FloorTile::from_glyph(*cell) {
+                    Some(tile) => {
+                        *cell = ' ';
+                        tile
+                    }
+                    None => FloorTile::Normal,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn floor_at(floor: &[Vec<FloorTile>], row: usize, col: usize) -> FloorTile {
+    floor.get(row).and_then(|cells| cells.get(col)).copied().unwrap_or(FloorTile::Normal)
+}
+
+pub fn floor_under(floor: &[Vec<FloorTile>], player: &Player, block_size: usize) -> FloorTile {
+    let row = (player.pos.y / block_size as f32).floor() as usize;
+    let col = (player.pos.x / block_size as f32).floor() as usize;
+    floor_at(floor, row, col)
+}
@@ -0,0 +1,145 @@
+use once_cell::sync::Lazy;
+use rusttype::{point, Font, GlyphId, Scale};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+static FONT_DATA: &[u8] = include_bytes!("../assets/Sterion.ttf");
+
+// La fuente se interpreta una sola vez y los glifos se rasterizan la primera vez que se
+// usan con cada tamaño
+pub static FONT: Lazy<FontCache> =
+    Lazy::new(|| FontCache::new(Font::try_from_bytes(FONT_DATA).expect("Error loading font")));
+
+// Un glifo ya rasterizado: cobertura de 0 a 255 por pixel y su caja relativa al punto de
+// partida sobre la línea base
+pub struct GlyphBitmap {
+    pub left: i32,
+    pub top: i32,
+    pub width: usize,
+    pub height: usize,
+    pub coverage: Vec<u8>,
+    pub advance: f32,
+}
+
+// La escala se guarda por bits porque f32 no es Hash
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    glyph: GlyphId,
+    scale: (u32, u32),
+}
+
+// Un glifo listo para dibujar en una posición de pixel entera
+pub struct PlacedGlyph {
+    pub x: i32,
+    pub y: i32,
+    pub bitmap: Arc<GlyphBitmap>,
+}
+
+pub struct FontCache {
+    font: Font<'static>,
+    atlas: Mutex<HashMap<GlyphKey, Arc<GlyphBitmap>>>,
+}
+
+impl FontCache {
+    pub fn new(font: Font<'static>) -> Self {
+        Self { font, atlas: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn ascent(&self, scale: Scale) -> f32 {
+        self.font.v_metrics(scale).ascent
+    }
+
+    // Distancia entre las líneas base de dos líneas seguidas
+    pub fn line_height(&self, scale: Scale) -> f32 {
+        let metrics = self.font.v_metrics(scale);
+        metrics.ascent - metrics.descent + metrics.line_gap
+    }
+
+    // Lo que avanza la pluma al escribir la línea, espacios e interletrado incluidos
+    pub fn width(&self, text: &str, scale: Scale) -> f32 {
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = self.font.glyph(c).id();
+            if let Some(previous) = previous {
+                width += self.font.pair_kerning(scale, previous, id);
+            }
+            width += self.glyph(id, scale).advance;
+            previous = Some(id);
+        }
+        width
+    }
+
+    // Parte el texto en líneas que no pasen de `max_width`, cortando solo entre palabras;
+    // una palabra más ancha que el límite queda sola en su línea. Los saltos de línea del
+    // texto se respetan.
+    pub fn wrap(&self, text: &str, scale: Scale, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                if !line.is_empty() {
+                    let candidate = format!("{} {}", line, word);
+                    if self.width(&candidate, scale) <= max_width {
+                        line = candidate;
+                        continue;
+                    }
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push_str(word);
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    fn glyph(&self, glyph: GlyphId, scale: Scale) -> Arc<GlyphBitmap> {
+        let key = GlyphKey { glyph, scale: (scale.x.to_bits(), scale.y.to_bits()) };
+        let mut atlas = self.atlas.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        atlas.entry(key).or_insert_with(|| Arc::new(self.rasterize(glyph, scale))).clone()
+    }
+
+    fn rasterize(&self, glyph: GlyphId, scale: Scale) -> GlyphBitmap {
+        let scaled = self.font.glyph(glyph).scaled(scale);
+        let advance = scaled.h_metrics().advance_width;
+        let positioned = scaled.positioned(point(0.0, 0.0));
+
+        let Some(bounds) = positioned.pixel_bounding_box() else {
+            let coverage = Vec::new();
+            return GlyphBitmap { left: 0, top: 0, width: 0, height: 0, coverage, advance };
+        };
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+        let mut coverage = vec![0; width * height];
+        positioned.draw(|x, y, value| {
+            coverage[y as usize * width + x as usize] = (value.clamp(0.0, 1.0) * 255.0) as u8;
+        });
+
+        GlyphBitmap { left: bounds.min.x, top: bounds.min.y, width, height, coverage, advance }
+    }
+
+    // Coloca cada carácter sobre la línea base que pasa por `y`, con el mismo interletrado
+    // que `Font::layout`
+    pub fn layout(&self, text: &str, x: f32, y: f32, scale: Scale) -> Vec<PlacedGlyph> {
+        let mut placed = Vec::with_capacity(text.len());
+        let mut pen = x;
+        let mut previous = None;
+
+        for c in text.chars() {
+            let id = self.font.glyph(c).id();
+            if let Some(previous) = previous {
+                pen += self.font.pair_kerning(scale, previous, id);
+            }
+            let bitmap = self.glyph(id, scale);
+            let advance = bitmap.advance;
+            placed.push(PlacedGlyph {
+                x: pen.round() as i32 + bitmap.left,
+                y: y.round() as i32 + bitmap.top,
+                bitmap,
+            });
+            pen += advance;
+            previous = Some(id);
+        }
+        placed
+    }
+}
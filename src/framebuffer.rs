@@ -1,4 +1,34 @@
-use rusttype::{Font, Scale, point, PositionedGlyph};
+use rusttype::Scale;
+use crate::font::{PlacedGlyph, FONT};
+use crate::texture::{Filter, Texture};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// Colores opcionales para que el texto se lea sobre cualquier fondo, y cómo se acomoda
+// cada línea dentro del ancho disponible en `draw_paragraph`
+#[derive(Clone, Copy)]
+pub struct TextStyle {
+    pub color: u32,
+    pub outline: Option<u32>,
+    pub shadow: Option<u32>,
+    pub align: Align,
+}
+
+impl TextStyle {
+    pub fn plain(color: u32) -> Self {
+        Self { color, outline: None, shadow: None, align: Align::Left }
+    }
+
+    // Texto del HUD: contorno y sombra oscuros para que resalte sobre las texturas claras
+    pub fn hud(color: u32) -> Self {
+        Self { color, outline: Some(0x000000), shadow: Some(0x000000), align: Align::Left }
+    }
+}
 
 pub struct Framebuffer {
     pub width: usize,
@@ -19,6 +49,45 @@ impl Framebuffer {
         }
     }
 
+    // Cambia el tamaño conservando los colores; el contenido queda en negro
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.buffer = vec![0; width * height];
+        }
+    }
+
+    // Cubre todo el buffer con `source` estirado a este tamaño
+    pub fn scale_from(&mut self, source: &Framebuffer, filter: Filter) {
+        if (source.width, source.height) == (self.width, self.height) {
+            self.buffer.copy_from_slice(&source.buffer);
+            return;
+        }
+        let sx = source.width as f32 / self.width as f32;
+        let sy = source.height as f32 / self.height as f32;
+        let texel = |x: usize, y: usize| source.buffer[y * source.width + x];
+        for (y, row) in self.buffer.chunks_mut(self.width).enumerate() {
+            // Centro del pixel destino en coordenadas de la fuente
+            let fy = ((y as f32 + 0.5) * sy - 0.5).clamp(0.0, (source.height - 1) as f32);
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let fx = ((x as f32 + 0.5) * sx - 0.5).clamp(0.0, (source.width - 1) as f32);
+                *pixel = match filter {
+                    Filter::Nearest => texel(fx.round() as usize, fy.round() as usize),
+                    Filter::Bilinear => {
+                        let (x0, y0) = (fx as usize, fy as usize);
+                        let x1 = (x0 + 1).min(source.width - 1);
+                        let y1 = (y0 + 1).min(source.height - 1);
+                        let (tx, ty) = (fx.fract(), fy.fract());
+                        let top = lerp(texel(x0, y0), texel(x1, y0), tx);
+                        let bottom = lerp(texel(x0, y1), texel(x1, y1), tx);
+                        lerp(top, bottom, ty)
+                    }
+                };
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         self.buffer.fill(self.background_color);
     }
@@ -29,52 +98,285 @@ impl Framebuffer {
         }
     }
 
+    // Como `point`, pero acepta coordenadas fuera de la pantalla y las descarta
+    fn plot(&mut self, x: i32, y: i32) {
+        if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+            self.point(x, y);
+        }
+    }
+
+    // Línea de Bresenham entre los dos extremos, ambos incluidos
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let (mut x, mut y) = (x0, y0);
+        let mut error = dx + dy;
+        loop {
+            self.plot(x, y);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize) {
+        let left = x.clamp(0, self.width as i32) as usize;
+        let top = y.clamp(0, self.height as i32) as usize;
+        let right = (x + width as i32).clamp(0, self.width as i32) as usize;
+        let bottom = (y + height as i32).clamp(0, self.height as i32) as usize;
+        if left >= right {
+            return;
+        }
+        for row in self.buffer.chunks_mut(self.width).take(bottom).skip(top) {
+            row[left..right].fill(self.current_color);
+        }
+    }
+
+    // Solo el borde de un pixel de grosor
+    pub fn rect(&mut self, x: i32, y: i32, width: usize, height: usize) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let right = x + width as i32 - 1;
+        let bottom = y + height as i32 - 1;
+        self.fill_rect(x, y, width, 1);
+        self.fill_rect(x, bottom, width, 1);
+        self.fill_rect(x, y, 1, height);
+        self.fill_rect(right, y, 1, height);
+    }
+
+    // Circunferencia por el algoritmo del punto medio
+    pub fn circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        let (mut x, mut y) = (radius, 0);
+        let mut error = 1 - radius;
+        while x >= y {
+            // Cada punto calculado en un octante se refleja en los otros siete
+            let octants = [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)];
+            for (px, py) in octants {
+                self.plot(cx + px, cy + py);
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        for dy in -radius..=radius {
+            let half = ((radius * radius - dy * dy) as f32).sqrt() as i32;
+            self.fill_rect(cx - half, cy + dy, (2 * half + 1) as usize, 1);
+        }
+    }
+
+    pub fn triangle(&mut self, a: (i32, i32), b: (i32, i32), c: (i32, i32)) {
+        self.line(a.0, a.1, b.0, b.1);
+        self.line(b.0, b.1, c.0, c.1);
+        self.line(c.0, c.1, a.0, a.1);
+    }
+
+    // Rellena los pixeles cuyo centro cae dentro del triángulo, en cualquier orden de vértices
+    pub fn fill_triangle(&mut self, a: (i32, i32), b: (i32, i32), c: (i32, i32)) {
+        let edge = |p: (i32, i32), q: (i32, i32), x: i32, y: i32| {
+            (q.0 - p.0) as i64 * (y - p.1) as i64 - (q.1 - p.1) as i64 * (x - p.0) as i64
+        };
+        let area = edge(a, b, c.0, c.1);
+        if area == 0 {
+            return;
+        }
+
+        let left = a.0.min(b.0).min(c.0).max(0);
+        let right = a.0.max(b.0).max(c.0).min(self.width as i32 - 1);
+        let top = a.1.min(b.1).min(c.1).max(0);
+        let bottom = a.1.max(b.1).max(c.1).min(self.height as i32 - 1);
+        for y in top..=bottom {
+            for x in left..=right {
+                let w = [edge(b, c, x, y), edge(c, a, x, y), edge(a, b, x, y)];
+                let inside = if area > 0 {
+                    w.iter().all(|&w| w >= 0)
+                } else {
+                    w.iter().all(|&w| w <= 0)
+                };
+                if inside {
+                    self.plot(x, y);
+                }
+            }
+        }
+    }
+
+    // Copia la textura escalada al rectángulo dado, mezclando según su alfa
+    pub fn blit(&mut self, texture: &Texture, x: i32, y: i32, width: usize, height: usize) {
+        let level = texture.mip_level(width.max(height) as f32);
+        let left = x.max(0);
+        let top = y.max(0);
+        let right = (x + width as i32).min(self.width as i32);
+        let bottom = (y + height as i32).min(self.height as i32);
+        for py in top..bottom {
+            let v = ((py - y) as f32 + 0.5) / height as f32;
+            for px in left..right {
+                let u = ((px - x) as f32 + 0.5) / width as f32;
+                let color = texture.sample(u, v, level, Filter::Nearest);
+                self.blend_point(px as usize, py as usize, color);
+            }
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
 
+    // El buffer solo guarda RGB; el alfa de las texturas se aprovecha en `blend_point`
     pub fn set_current_color(&mut self, color: u32) {
-        self.current_color = color;
+        self.current_color = color & 0xffffff;
     }
 
-    pub fn drawtext(&mut self, text: &str, x: usize, y: usize, scale: Scale, color: u32) {
-        let font = Self::load_font();
-        let v_metrics = font.v_metrics(scale);
-        let offset = point(x as f32, y as f32 + v_metrics.ascent);
+    // `color` viene como 0xAARRGGBB y se mezcla con lo que ya hay en el pixel
+    pub fn blend_point(&mut self, x: usize, y: usize, color: u32) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            self.buffer[index] = blend(self.buffer[index], color, color >> 24);
+        }
+    }
 
-        let glyphs = font.layout(text, scale, offset);
+    // Mezcla todo el buffer hacia `color`; amount = 1.0 lo cubre por completo
+    pub fn fade(&mut self, color: u32, amount: f32) {
+        let alpha = (amount.clamp(0.0, 1.0) * 255.0) as u32;
+        for pixel in self.buffer.iter_mut() {
+            *pixel = blend(*pixel, color, alpha);
+        }
+    }
 
-        self.draw_glyphs(glyphs, color);
+    // Tiñe la pantalla de `color` con más fuerza hacia los bordes, como un destello al
+    // recibir daño; amount = 1.0 cubre por completo las esquinas
+    pub fn flash(&mut self, color: u32, amount: f32) {
+        let amount = amount.clamp(0.0, 1.0);
+        let cx = self.width as f32 / 2.0;
+        let cy = self.height as f32 / 2.0;
+        for (y, row) in self.buffer.chunks_mut(self.width).enumerate() {
+            let dy = (y as f32 - cy) / cy;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let dx = (x as f32 - cx) / cx;
+                let edge = ((dx * dx + dy * dy) / 2.0).min(1.0);
+                let alpha = (amount * (0.35 + 0.65 * edge) * 255.0) as u32;
+                *pixel = blend(*pixel, color, alpha);
+            }
+        }
     }
 
-    pub fn text_width(&self, text: &str, scale: Scale) -> f32 {
-        let font = Self::load_font();
-        font.layout(text, scale, point(0.0, 0.0))
-            .map(|g| g.pixel_bounding_box().map(|b| b.width() as f32).unwrap_or(0.0))
-            .sum()
+    pub fn drawtext(&mut self, text: &str, x: usize, y: usize, scale: Scale, color: u32) {
+        self.drawtext_styled(text, x, y, scale, TextStyle::plain(color));
     }
 
-    fn load_font() -> Font<'static> {
-        static FONT_DATA: &'static [u8] = include_bytes!("../assets/Sterion.ttf");
-        Font::try_from_bytes(FONT_DATA).expect("Error loading font")
+    // Dibuja primero la sombra, después el contorno y encima el texto, cada capa mezclada
+    // según la cobertura de cada pixel del glifo
+    pub fn drawtext_styled(
+        &mut self,
+        text: &str,
+        x: usize,
+        y: usize,
+        scale: Scale,
+        style: TextStyle,
+    ) {
+        let baseline = y as f32 + FONT.ascent(scale);
+        let glyphs = FONT.layout(text, x as f32, baseline, scale);
+
+        if let Some(shadow) = style.shadow {
+            let offset = (scale.y / 16.0).round().max(1.0) as i32;
+            self.draw_glyphs(&glyphs, offset, offset, shadow);
+        }
+        if let Some(outline) = style.outline {
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                self.draw_glyphs(&glyphs, dx, dy, outline);
+            }
+        }
+        self.draw_glyphs(&glyphs, 0, 0, style.color);
     }
 
-    fn draw_glyphs<'a>(&mut self, glyphs: impl Iterator<Item = PositionedGlyph<'a>>, color: u32) {
+    fn draw_glyphs(&mut self, glyphs: &[PlacedGlyph], dx: i32, dy: i32, color: u32) {
         for glyph in glyphs {
-            if let Some(bounding_box) = glyph.pixel_bounding_box() {
-                glyph.draw(|gx, gy, gv| {
-                    if gv > 0.5 {
-                        let gx = gx as i32 + bounding_box.min.x;
-                        let gy = gy as i32 + bounding_box.min.y;
-
-                        if let (Ok(gx), Ok(gy)) = (usize::try_from(gx), usize::try_from(gy)) {
-                            if gx < self.width && gy < self.height {
-                                self.buffer[gy * self.width + gx] = color;
-                            }
+            let bitmap = &glyph.bitmap;
+            for (gy, row) in bitmap.coverage.chunks(bitmap.width.max(1)).enumerate() {
+                for (gx, &coverage) in row.iter().enumerate() {
+                    let px = glyph.x + dx + gx as i32;
+                    let py = glyph.y + dy + gy as i32;
+                    if let (Ok(px), Ok(py)) = (usize::try_from(px), usize::try_from(py)) {
+                        if px < self.width && py < self.height {
+                            let index = py * self.width + px;
+                            self.buffer[index] = blend(self.buffer[index], color, coverage as u32);
                         }
                     }
-                });
+                }
             }
         }
     }
+
+    // Ancho de la línea más larga
+    pub fn text_width(&self, text: &str, scale: Scale) -> f32 {
+        text.lines().map(|line| FONT.width(line, scale)).fold(0.0, f32::max)
+    }
+
+    // Alto que ocupa el texto repartido en líneas de a lo sumo `max_width`
+    pub fn text_height(&self, text: &str, scale: Scale, max_width: f32) -> f32 {
+        FONT.wrap(text, scale, max_width).len() as f32 * FONT.line_height(scale)
+    }
+
+    // Dibuja el texto en la franja que empieza en `x` con ancho `width`, partiéndolo en
+    // líneas y alineando cada una según `style.align`. Devuelve el alto que ocupó.
+    pub fn draw_paragraph(
+        &mut self,
+        text: &str,
+        x: usize,
+        y: usize,
+        width: usize,
+        scale: Scale,
+        style: TextStyle,
+    ) -> f32 {
+        let line_height = FONT.line_height(scale);
+        let lines = FONT.wrap(text, scale, width as f32);
+        for (i, line) in lines.iter().enumerate() {
+            let free = (width as f32 - FONT.width(line, scale)).max(0.0);
+            let offset = match style.align {
+                Align::Left => 0.0,
+                Align::Center => free / 2.0,
+                Align::Right => free,
+            };
+            let line_y = y as f32 + i as f32 * line_height;
+            self.drawtext_styled(line, x + offset as usize, line_y as usize, scale, style);
+        }
+        lines.len() as f32 * line_height
+    }
+}
+
+fn lerp(a: u32, b: u32, t: f32) -> u32 {
+    blend(a, b, (t * 255.0) as u32)
+}
+
+// Mezcla `src` sobre `dst` con opacidad `alpha` (0 a 255); el resultado queda sin alfa
+fn blend(dst: u32, src: u32, alpha: u32) -> u32 {
+    match alpha {
+        0 => dst,
+        255.. => src & 0xffffff,
+        _ => {
+            // Escala el alfa a 0..=256 para poder dividir con un corrimiento
+            let a = alpha + (alpha >> 7);
+            let inv = 256 - a;
+            let rb = (((src & 0xff00ff) * a + (dst & 0xff00ff) * inv) >> 8) & 0xff00ff;
+            let g = (((src & 0x00ff00) * a + (dst & 0x00ff00) * inv) >> 8) & 0x00ff00;
+            rb | g
+        }
+    }
 }
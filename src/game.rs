@@ -0,0 +1,728 @@
+use nalgebra_glm::Vec2;
+use once_cell::sync::Lazy;
+use rusttype::Scale;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::framebuffer::{Align, Framebuffer, TextStyle};
+use crate::maze::load_level;
+use crate::player::{process_events, Player, MAX_HEALTH};
+use crate::audio::{AudioPlayer, SoundEffect};
+use crate::cast_function::cast_ray;
+use crate::texture::{load_texture, take_load_errors, Filter, Texture, TextureRegistry};
+use crate::render::{fog, render_view, RenderOptions, Scene};
+use crate::camera::Camera;
+use crate::door::{find_doors, is_door, update_doors, Door};
+use crate::item::{extract_items, ItemKind, KeyColor};
+use crate::solver::{is_goal_adjacent, validate_level};
+use crate::teleport::{extract_teleporters, try_teleport, Teleporter};
+use crate::sprite::{SpriteFrame, SpriteSheet};
+use crate::entity::{EntitySprite, World, WorldEvent};
+use crate::enemy::Difficulty;
+use crate::floor::{extract_floor, floor_at, floor_under, FloorTile, DARK_VIEW_DISTANCE};
+use crate::platform::{Key, Platform};
+
+pub const WINDOW_WIDTH: usize = 1200;
+pub const WINDOW_HEIGHT: usize = 720;
+// Escala del render 3D respecto a la ventana; el HUD y el texto siempre van a tamaño real
+const MIN_RENDER_SCALE: f32 = 0.25;
+const MAX_RENDER_SCALE: f32 = 2.0;
+const RENDER_SCALE_STEP: f32 = 0.25;
+const BLOCK_SIZE: usize = 100;
+const FRAME_DURATION: Duration = Duration::from_millis(15);
+const MESSAGE_SECONDS: f32 = 3.0;
+
+static WALL_TEXTURES: Lazy<TextureRegistry> = Lazy::new(|| {
+    TextureRegistry::load("assets/textures.txt").unwrap_or_else(|e| {
+        eprintln!("Failed to load texture manifest: {}", e);
+        TextureRegistry::default()
+    })
+});
+static PLACEHOLDER: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::placeholder()));
+static ALIEN_SHEET: Lazy<SpriteSheet> = Lazy::new(|| {
+    SpriteSheet::load("assets/alien.sheet").unwrap_or_else(|e| {
+        eprintln!("Failed to load sprite sheet: {}", e);
+        SpriteSheet::single(load_texture("assets/alien.png"))
+    })
+});
+static KEY_RED: Lazy<Arc<Texture>> = Lazy::new(|| load_texture("assets/key_red.png"));
+static KEY_BLUE: Lazy<Arc<Texture>> = Lazy::new(|| load_texture("assets/key_blue.png"));
+static KEY_YELLOW: Lazy<Arc<Texture>> = Lazy::new(|| load_texture("assets/key_yellow.png"));
+static TELEPORTER: Lazy<Arc<Texture>> = Lazy::new(|| load_texture("assets/teleporter.png"));
+
+// Carga todas las texturas antes de abrir la ventana para reportar de una vez cada
+// archivo que falte, en vez de descubrirlos uno por uno al dibujar.
+pub fn preload_textures() {
+    Lazy::force(&WALL_TEXTURES);
+    Lazy::force(&ALIEN_SHEET);
+    for texture in [&KEY_RED, &KEY_BLUE, &KEY_YELLOW, &TELEPORTER] {
+        Lazy::force(texture);
+    }
+
+    let errors = take_load_errors();
+    if !errors.is_empty() {
+        eprintln!("{} texture(s) could not be loaded, using placeholders:", errors.len());
+        for e in &errors {
+            eprintln!("  {}", e);
+        }
+    }
+}
+
+fn walls_minimap(
+    framebuffer: &mut Framebuffer,
+    xo: i32,
+    yo: i32,
+    block_size: usize,
+    cell: char,
+) {
+    if cell != ' ' {
+        let color = match KeyColor::from_door_glyph(cell) {
+            Some(key) => key.color(),
+            None if is_door(cell) => 0xb4a23c,
+            None => 0x14544b,
+        };
+        framebuffer.set_current_color(color);
+        framebuffer.fill_rect(xo, yo, block_size, block_size);
+    }
+}
+
+fn maze_to_minimap(
+    framebuffer: &mut Framebuffer,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    player: &Player,
+) {
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            walls_minimap(
+                framebuffer,
+                (col * BLOCK_SIZE) as i32,
+                (row * BLOCK_SIZE) as i32,
+                BLOCK_SIZE,
+                cell,
+            );
+        }
+    }
+    framebuffer.set_current_color(0xFFFFFF);
+    framebuffer.point(player.pos.x as usize, player.pos.y as usize);
+
+    let num_rays = 150;
+    for i in 0..num_rays {
+        let current_ray = i as f32 / num_rays as f32;
+        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
+        cast_ray(framebuffer, maze, doors, player, a, BLOCK_SIZE, true);
+    }
+}
+
+fn minimap_function(
+    framebuffer: &mut Framebuffer,
+    maze: &[Vec<char>],
+    floor: &[Vec<FloorTile>],
+    teleporters: &[Teleporter],
+    block_size: usize,
+    player: &Player,
+) {
+    // En i32 porque en una ventana muy chica el minimapa queda en parte fuera de pantalla;
+    // los primitivos recortan
+    let minimap_size = 150;
+    let minimap_x = framebuffer.width as i32 - minimap_size as i32 - 80;
+    let minimap_y = framebuffer.height as i32 - minimap_size as i32 - 10;
+
+    framebuffer.set_current_color(0x56615d);
+    framebuffer.fill_rect(minimap_x, minimap_y, minimap_size + 70, minimap_size);
+
+    let scale = minimap_size as f32 / (maze.len() as f32 * block_size as f32);
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            let cell_x = (col as f32 * block_size as f32 * scale) as i32;
+            let cell_y = (row as f32 * block_size as f32 * scale) as i32;
+            let mini_block_size = (block_size as f32 * scale) as usize;
+            walls_minimap(
+                framebuffer,
+                minimap_x + cell_x,
+                minimap_y + cell_y,
+                mini_block_size,
+                cell,
+            );
+        }
+    }
+
+    let mini_block_size = (block_size as f32 * scale) as usize;
+    for (row, cells) in maze.iter().enumerate() {
+        for col in 0..cells.len() {
+            if let Some(color) = floor_minimap_color(floor_at(floor, row, col)) {
+                framebuffer.set_current_color(color);
+                let cell_x = minimap_x + (col as f32 * block_size as f32 * scale) as i32;
+                let cell_y = minimap_y + (row as f32 * block_size as f32 * scale) as i32;
+                let size = mini_block_size;
+                framebuffer.fill_rect(cell_x, cell_y, size, size);
+            }
+        }
+    }
+
+    framebuffer.set_current_color(0x3ce6ff);
+    for pad in teleporters {
+        let cell_x = minimap_x + (pad.col as f32 * block_size as f32 * scale) as i32;
+        let cell_y = minimap_y + (pad.row as f32 * block_size as f32 * scale) as i32;
+        let inner = mini_block_size.saturating_sub(2);
+        framebuffer.fill_rect(cell_x + 1, cell_y + 1, inner, inner);
+    }
+
+    // El jugador es un punto con una línea hacia donde mira
+    framebuffer.set_current_color(0xFFFFFF);
+    let player_x = minimap_x + (player.pos.x * scale) as i32;
+    let player_y = minimap_y + (player.pos.y * scale) as i32;
+    let (sin_a, cos_a) = player.a.sin_cos();
+    let tip_x = player_x + (cos_a * mini_block_size as f32 * 0.6) as i32;
+    let tip_y = player_y + (sin_a * mini_block_size as f32 * 0.6) as i32;
+    framebuffer.line(player_x, player_y, tip_x, tip_y);
+    framebuffer.fill_circle(player_x, player_y, 2);
+}
+
+// Lado de los sprites, en bloques
+const SPRITE_SIZE: f32 = 0.35;
+// Alfa mínimo para que un texel cuente como opaco al recortar
+const ALPHA_CUTOFF: u32 = 128;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SpriteBlend {
+    // Cada texel se dibuja entero o se descarta; bordes nítidos y más barato
+    AlphaTest,
+    // Los texels translúcidos se mezclan con lo que ya hay detrás
+    AlphaBlend,
+}
+
+fn render_object(
+    framebuffer: &mut Framebuffer,
+    camera: &Camera,
+    pos: &Vec2,
+    sprite: SpriteFrame,
+    blend: SpriteBlend,
+    view_distance: f32,
+    z_buffer: &[f32],
+) {
+    // Detrás de la cámara; los que quedan a medias fuera de la pantalla se recortan abajo
+    let Some((center_x, sprite_d)) = camera.project(*pos) else {
+        return;
+    };
+    if sprite_d >= view_distance * BLOCK_SIZE as f32 {
+        return;
+    }
+
+    // Con la misma proyección que las paredes, parado sobre el piso
+    let sprite_size = camera.projected_size(SPRITE_SIZE, sprite_d);
+    let start_x = center_x - sprite_size / 2.0;
+    let start_y = camera.screen_y(0.0, sprite_d) - sprite_size;
+
+    if start_x + sprite_size <= 0.0 || start_x >= framebuffer.width as f32 {
+        return;
+    }
+
+    // Se recorta a la pantalla, pero las coordenadas de textura siguen midiéndose desde
+    // el borde real del sprite para que no se deforme al salir por la izquierda o arriba
+    let end_x = ((start_x + sprite_size) as usize).min(framebuffer.width);
+    let end_y = ((start_y + sprite_size) as usize).min(framebuffer.height);
+    let first_x = start_x.max(0.0) as usize;
+    let first_y = start_y.max(0.0) as usize;
+    let level = sprite.mip_level(sprite_size);
+
+    for x in first_x..end_x {
+        for y in first_y..end_y {
+            // Por pixel, para que asome por encima de una pared más baja que él
+            if sprite_d >= z_buffer[x * framebuffer.height + y] {
+                continue;
+            }
+            let tx = (x as f32 - start_x) / sprite_size;
+            let ty = (y as f32 - start_y) / sprite_size;
+            let color = sprite.sample(tx, ty, level, Filter::Nearest);
+            let color = fog(color, sprite_d, view_distance, BLOCK_SIZE);
+            let alpha = color >> 24;
+            match blend {
+                SpriteBlend::AlphaTest if alpha >= ALPHA_CUTOFF => {
+                    framebuffer.set_current_color(color);
+                    framebuffer.point(x, y);
+                }
+                SpriteBlend::AlphaBlend if alpha > 0 => framebuffer.blend_point(x, y, color),
+                _ => {}
+            }
+        }
+    }
+}
+
+// Los sprites solo se ocultan detrás de las paredes; entre ellos se tapan dibujándolos
+// del más lejano al más cercano, así el orden de las listas no importa.
+fn position_sprites(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    world: &World,
+    teleporters: &[Teleporter],
+    view_distance: f32,
+    z_buffer: &[f32],
+) {
+    let camera = Camera::new(player, framebuffer.width, framebuffer.height, BLOCK_SIZE);
+    let mut sprites: Vec<(Vec2, SpriteFrame, SpriteBlend)> = Vec::new();
+
+    for entity in &world.entities {
+        let sprite = match entity.sprite {
+            Some(EntitySprite::Alien) => {
+                let frame = entity.animator.frame(&ALIEN_SHEET, entity.view_angle(player));
+                (frame, SpriteBlend::AlphaBlend)
+            }
+            Some(EntitySprite::Item(ItemKind::Key(color))) => {
+                (SpriteFrame::whole(key_texture(color)), SpriteBlend::AlphaTest)
+            }
+            None => continue,
+        };
+        sprites.push((entity.pos, sprite.0, sprite.1));
+    }
+
+    for pad in teleporters {
+        let frame = SpriteFrame::whole(&TELEPORTER);
+        sprites.push((pad.pos(BLOCK_SIZE), frame, SpriteBlend::AlphaBlend));
+    }
+
+    sprites.sort_by(|(a, _, _), (b, _, _)| {
+        let distance_a = (a - player.pos).norm_squared();
+        let distance_b = (b - player.pos).norm_squared();
+        distance_b.total_cmp(&distance_a)
+    });
+
+    for (pos, frame, blend) in &sprites {
+        render_object(framebuffer, &camera, pos, *frame, *blend, view_distance, z_buffer);
+    }
+}
+
+fn key_texture(color: KeyColor) -> &'static Texture {
+    match color {
+        KeyColor::Red => &KEY_RED,
+        KeyColor::Blue => &KEY_BLUE,
+        KeyColor::Yellow => &KEY_YELLOW,
+    }
+}
+
+fn keys_hud(framebuffer: &mut Framebuffer, player: &Player) {
+    let slot_size = 24;
+    let padding = 6;
+    let strip_x = 10;
+    let strip_y = framebuffer.height as i32 - (slot_size + padding * 2 + 10) as i32;
+    let strip_width = KeyColor::ALL.len() * (slot_size + padding) + padding;
+
+    framebuffer.set_current_color(0x213b31);
+    framebuffer.fill_rect(strip_x, strip_y, strip_width, slot_size + padding * 2);
+
+    for (i, color) in KeyColor::ALL.iter().enumerate() {
+        let slot_x = strip_x + (padding + i * (slot_size + padding)) as i32;
+        let slot_y = strip_y + padding as i32;
+
+        // Las llaves que faltan se muestran solo con el contorno
+        framebuffer.set_current_color(color.color());
+        framebuffer.rect(slot_x, slot_y, slot_size, slot_size);
+        if player.has_key(*color) {
+            framebuffer.blit(key_texture(*color), slot_x, slot_y, slot_size, slot_size);
+        }
+    }
+}
+
+fn floor_minimap_color(tile: FloorTile) -> Option<u32> {
+    match tile {
+        FloorTile::Normal => None,
+        FloorTile::Slow => Some(0x5f4b28),
+        FloorTile::Conveyor(_) => Some(0xe6be1e),
+        FloorTile::Hazard => Some(0xe8661c),
+        FloorTile::Dark => Some(0x080810),
+    }
+}
+
+fn health_hud(framebuffer: &mut Framebuffer, player: &Player) {
+    let bar_width = 200;
+    let bar_height = 14;
+    let bar_x = 10;
+    let bar_y = framebuffer.height as i32 - (24 + 6 * 2 + 10 + bar_height + 8) as i32;
+    let filled = bar_width * player.health as usize / MAX_HEALTH as usize;
+
+    framebuffer.set_current_color(0x213b31);
+    framebuffer.fill_rect(bar_x, bar_y, bar_width, bar_height);
+    // Parpadea cinco veces por segundo mientras dura la invulnerabilidad
+    let blink = player.is_invulnerable() && (player.invulnerable * 10.0) as u32 % 2 == 1;
+    framebuffer.set_current_color(if blink { 0xff8a8a } else { 0xd23232 });
+    framebuffer.fill_rect(bar_x, bar_y, filled, bar_height);
+}
+
+// Ajusta el framebuffer al tamaño actual de la ventana
+fn fit_to_platform(framebuffer: &mut Framebuffer, platform: &impl Platform) {
+    let (width, height) = platform.size();
+    framebuffer.resize(width, height);
+}
+
+// Centra el texto en la pantalla, en ambos ejes
+fn draw_centered_text(framebuffer: &mut Framebuffer, text: &str, scale: Scale) {
+    let style = TextStyle { align: Align::Center, ..TextStyle::plain(0xFFFFFF) };
+    let width = framebuffer.width;
+    let height = framebuffer.text_height(text, scale, width as f32);
+    let y = ((framebuffer.height as f32 - height) / 2.0).max(0.0);
+    framebuffer.draw_paragraph(text, 0, y as usize, width, scale, style);
+}
+
+// Muestra unas líneas centradas durante unos segundos, o hasta que se cierre la ventana
+pub fn show_end_screen(
+    platform: &mut impl Platform,
+    framebuffer: &mut Framebuffer,
+    lines: &[&str],
+) {
+    let text = lines.join("\n");
+    platform.set_cursor_visible(true);
+
+    let end_time = Instant::now() + Duration::from_secs(5);
+    while platform.is_open() && Instant::now() < end_time {
+        if platform.poll_input().is_down(Key::Escape) {
+            break;
+        }
+
+        fit_to_platform(framebuffer, platform);
+        framebuffer.clear();
+        draw_centered_text(framebuffer, &text, Scale::uniform(32.0));
+        if let Err(e) = platform.present(framebuffer) {
+            eprintln!("Failed to present frame: {}", e);
+            break;
+        }
+
+        std::thread::sleep(Duration::from_millis(16));
+    }
+}
+
+// Devuelve true si el jugador quiere reintentar y false si prefiere salir
+pub fn game_over_screen(platform: &mut impl Platform, framebuffer: &mut Framebuffer) -> bool {
+    let text = "TE QUEDASTE SIN VIDA\nR x Reintentar ! Esc x Salir";
+    platform.set_cursor_visible(true);
+
+    while platform.is_open() {
+        let input = platform.poll_input();
+        if input.is_down(Key::Escape) {
+            break;
+        }
+        if input.is_down(Key::R) {
+            return true;
+        }
+
+        fit_to_platform(framebuffer, platform);
+        framebuffer.clear();
+        draw_centered_text(framebuffer, text, Scale::uniform(32.0));
+        if let Err(e) = platform.present(framebuffer) {
+            eprintln!("Failed to present frame: {}", e);
+            break;
+        }
+
+        std::thread::sleep(Duration::from_millis(16));
+    }
+    false
+}
+
+// Todo el juego, desde la pantalla de título, sobre cualquier backend. Devuelve el jugador
+// como quedó al terminar la última partida, o None si el nivel no se pudo cargar o la
+// pantalla de título no se pudo mostrar.
+pub fn run(platform: &mut impl Platform, sound: bool) -> Option<Player> {
+    let (audio_player, steps_player, door_sound) = if sound {
+        (
+            AudioPlayer::new("assets/music.mp3").expect("Failed to initialize AudioPlayer"),
+            AudioPlayer::new("assets/steps.mp3").expect("Failed to initialize AudioPlayer"),
+            SoundEffect::new("assets/door.wav").expect("Failed to initialize SoundEffect"),
+        )
+    } else {
+        (AudioPlayer::muted(), AudioPlayer::muted(), SoundEffect::muted())
+    };
+    audio_player.play();
+
+    let mut framebuffer = Framebuffer::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+    framebuffer.set_background_color(0x213b31);
+    // La vista 3D se dibuja aparte, a la escala de render, y después se estira a la ventana
+    let mut view = Framebuffer::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+    // Una distancia por pixel de `view`; render_view la reescribe entera en cada cuadro
+    let mut z_buffer: Vec<f32> = Vec::new();
+    let mut render_scale: f32 = 1.0;
+    let mut upscale_filter = Filter::Nearest;
+
+    let mut difficulty = Difficulty::Normal;
+
+    while platform.is_open() {
+        let frame_start_time = Instant::now();
+
+        let input = platform.poll_input();
+        if input.is_down(Key::Enter) {
+            break;
+        }
+        if input.is_down(Key::Num1) {
+            difficulty = Difficulty::Easy;
+        } else if input.is_down(Key::Num2) {
+            difficulty = Difficulty::Normal;
+        } else if input.is_down(Key::Num3) {
+            difficulty = Difficulty::Hard;
+        }
+
+        fit_to_platform(&mut framebuffer, platform);
+        framebuffer.clear();
+
+        let text = format!(
+            "BIENVENIDO A SPACE MAZE\nEnter x Jugar ! Esc x Salir\nDificultad {} (1 2 3)",
+            difficulty.name()
+        );
+        draw_centered_text(&mut framebuffer, &text, Scale::uniform(32.0));
+
+        if let Err(e) = platform.present(&framebuffer) {
+            eprintln!("Failed to present frame: {}", e);
+            return None;
+        }
+
+        let frame_end_time = Instant::now();
+        let frame_duration_actual = frame_end_time.duration_since(frame_start_time);
+        if frame_duration_actual < FRAME_DURATION {
+            std::thread::sleep(FRAME_DURATION - frame_duration_actual);
+        }
+    }
+
+    let mut mode = "3D";
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut options = RenderOptions {
+        filter: Filter::Nearest,
+        view_distance: f32::INFINITY,
+        threads: max_threads,
+    };
+
+    // Cada vuelta es una partida completa; reintentar tras perder vuelve a cargar el nivel
+    'run: loop {
+        let spawn_pos = Vec2::new(150.0, 150.0);
+        let spawn_angle = std::f32::consts::PI / 3.0;
+        let mut player = Player::new(spawn_pos, spawn_angle, std::f32::consts::PI / 3.0);
+
+        let level = match load_level("./maze.txt") {
+            Ok(level) => level,
+            Err(e) => {
+                eprintln!("Failed to load maze: {}", e);
+                return None;
+            }
+        };
+        let mut maze = level.maze;
+        let items = extract_items(&mut maze);
+        let floor = extract_floor(&mut maze);
+        let mut teleporters = match extract_teleporters(&mut maze) {
+            Ok(teleporters) => teleporters,
+            Err(e) => {
+                eprintln!("Invalid level: {}", e);
+                return None;
+            }
+        };
+
+        let spawn = (
+            (player.pos.y / BLOCK_SIZE as f32) as usize,
+            (player.pos.x / BLOCK_SIZE as f32) as usize,
+        );
+        let optimal_path = match validate_level(&maze, &items, &teleporters, spawn) {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("Invalid level: {}", e);
+                return None;
+            }
+        };
+        let mut teleport_fade: f32 = 0.0;
+        let mut damage_flash: f32 = 0.0;
+        let mut darkness: f32 = 0.0;
+
+        let mut doors = find_doors(&maze);
+        let mut world = match World::load(&level.directives, &items, &maze, BLOCK_SIZE) {
+            Ok(world) => world,
+            Err(e) => {
+                eprintln!("Invalid level: {}", e);
+                return None;
+            }
+        };
+        let mut message: Option<(String, f32)> = None;
+
+        let mut last_time = Instant::now();
+        let mut frame_count = 0;
+        let mut fps_text = String::new();
+        let mut previous_frame = Instant::now();
+
+        let title =
+            |scale: f32| format!("SPACE MAZE - {} - {:.0}%", difficulty.name(), scale * 100.0);
+        platform.set_title(&title(render_scale));
+        platform.set_cursor_visible(false);
+
+        while platform.is_open() {
+            let frame_start_time = Instant::now();
+            let dt = frame_start_time.duration_since(previous_frame).as_secs_f32();
+            previous_frame = frame_start_time;
+
+            let input = platform.poll_input();
+            if input.is_down(Key::Escape) {
+                break;
+            }
+            if input.is_down(Key::M) {
+                mode = if mode == "2D" { "3D" } else { "2D" };
+            }
+            if input.was_pressed(Key::F) {
+                options.filter = match options.filter {
+                    Filter::Nearest => Filter::Bilinear,
+                    Filter::Bilinear => Filter::Nearest,
+                };
+            }
+            // Alterna entre un solo hilo y todos, para comparar los FPS
+            if input.was_pressed(Key::T) {
+                options.threads = if options.threads == 1 { max_threads } else { 1 };
+            }
+            // - y + cambian la escala del render 3D
+            let step = match (input.was_pressed(Key::Minus), input.was_pressed(Key::Plus)) {
+                (true, false) => -RENDER_SCALE_STEP,
+                (false, true) => RENDER_SCALE_STEP,
+                _ => 0.0,
+            };
+            if step != 0.0 {
+                render_scale = (render_scale + step).clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+                platform.set_title(&title(render_scale));
+            }
+            if input.was_pressed(Key::U) {
+                upscale_filter = match upscale_filter {
+                    Filter::Nearest => Filter::Bilinear,
+                    Filter::Bilinear => Filter::Nearest,
+                };
+            }
+
+            let row = (player.pos.y / BLOCK_SIZE as f32) as usize;
+            let col = (player.pos.x / BLOCK_SIZE as f32) as usize;
+            if is_goal_adjacent(&maze, row, col) {
+                let route = format!("RUTA MINIMA: {} CASILLAS", optimal_path.len() - 1);
+                show_end_screen(
+                    platform,
+                    &mut framebuffer,
+                    &["FELICIDADES LLEGASTE A LA ESTACION", "CREADO POR GERCO", &route],
+                );
+                break;
+            }
+
+            process_events(
+                &input,
+                &mut player,
+                &maze,
+                &floor,
+                &mut doors,
+                BLOCK_SIZE,
+                &steps_player,
+            );
+            update_doors(&mut doors, &player, BLOCK_SIZE, &door_sound);
+            player.update(dt);
+            let mut caught = false;
+            for event in world.update(&mut player, &maze, &doors, difficulty, BLOCK_SIZE, dt) {
+                match event {
+                    WorldEvent::Message(text) => message = Some((text, MESSAGE_SECONDS)),
+                    WorldEvent::Caught { damaged } => {
+                        caught = true;
+                        if damaged {
+                            damage_flash = 1.0;
+                        }
+                    }
+                    WorldEvent::PickedUp(_) => {}
+                }
+            }
+            let tile = floor_under(&floor, &player, BLOCK_SIZE);
+            if player.damage(tile.damage()) {
+                damage_flash = 1.0;
+            }
+            // La oscuridad entra y sale de a poco; sin ella la vista no tiene límite
+            let target = if tile == FloorTile::Dark { 1.0 } else { 0.0 };
+            darkness += (target - darkness).clamp(-dt * 2.0, dt * 2.0);
+            options.view_distance = DARK_VIEW_DISTANCE / darkness;
+            if player.is_dead() {
+                if game_over_screen(platform, &mut framebuffer) {
+                    continue 'run;
+                }
+                return Some(player);
+            }
+            if caught {
+                if difficulty.caught_ends_run() {
+                    show_end_screen(platform, &mut framebuffer, &["TE ATRAPARON", "FIN DEL JUEGO"]);
+                    break;
+                }
+                player.pos = spawn_pos;
+                player.a = spawn_angle;
+                teleport_fade = 1.0;
+                message = Some(("TE ATRAPARON".to_string(), MESSAGE_SECONDS));
+            }
+            if try_teleport(&mut teleporters, &mut player, BLOCK_SIZE) {
+                teleport_fade = 1.0;
+            }
+
+            fit_to_platform(&mut framebuffer, platform);
+            framebuffer.clear();
+            if mode == "2D" {
+                maze_to_minimap(&mut framebuffer, &maze, &doors, &player);
+            } else {
+                let scaled = |size: usize| ((size as f32 * render_scale).round() as usize).max(1);
+                view.resize(scaled(framebuffer.width), scaled(framebuffer.height));
+                z_buffer.resize(view.width * view.height, f32::INFINITY);
+                let scene = Scene {
+                    maze: &maze,
+                    floor: &floor,
+                    doors: &doors,
+                    textures: &WALL_TEXTURES,
+                    placeholder: &PLACEHOLDER,
+                    block_size: BLOCK_SIZE,
+                };
+                render_view(&mut view, &scene, &player, options, &mut z_buffer);
+                position_sprites(
+                    &mut view,
+                    &player,
+                    &world,
+                    &teleporters,
+                    options.view_distance,
+                    &z_buffer,
+                );
+                framebuffer.scale_from(&view, upscale_filter);
+            }
+            if teleport_fade > 0.0 {
+                framebuffer.fade(0x000000, teleport_fade);
+                teleport_fade = (teleport_fade - dt * 3.0).max(0.0);
+            }
+            if damage_flash > 0.0 {
+                framebuffer.flash(0xff0000, damage_flash * 0.6);
+                damage_flash = (damage_flash - dt * 2.5).max(0.0);
+            }
+            minimap_function(&mut framebuffer, &maze, &floor, &teleporters, BLOCK_SIZE, &player);
+            keys_hud(&mut framebuffer, &player);
+            health_hud(&mut framebuffer, &player);
+
+            frame_count += 1;
+            let current_time = Instant::now();
+            let elapsed = current_time.duration_since(last_time);
+
+            if elapsed >= Duration::from_secs(1) {
+                let fps = frame_count as f64 / elapsed.as_secs_f64();
+                fps_text = format!("FPS: {:.0}", fps);
+                last_time = current_time;
+                frame_count = 0;
+            }
+
+            let hud_text = TextStyle::hud(0xFFFFFF);
+            framebuffer.drawtext_styled(&fps_text, 10, 10, Scale::uniform(32.0), hud_text);
+            if let Some((text, remaining)) = &mut message {
+                let style = TextStyle { align: Align::Center, ..hud_text };
+                let margin = framebuffer.width / 8;
+                let width = framebuffer.width - 2 * margin;
+                framebuffer.draw_paragraph(text, margin, 60, width, Scale::uniform(28.0), style);
+                *remaining -= dt;
+                if *remaining <= 0.0 {
+                    message = None;
+                }
+            }
+
+            if let Err(e) = platform.present(&framebuffer) {
+                eprintln!("Failed to present frame: {}", e);
+                break;
+            }
+
+            let frame_end_time = Instant::now();
+            let frame_duration_actual = frame_end_time.duration_since(frame_start_time);
+            if frame_duration_actual < FRAME_DURATION {
+                std::thread::sleep(FRAME_DURATION - frame_duration_actual);
+            }
+        }
+        return Some(player);
+    }
+}
@@ -0,0 +1,77 @@
+use nalgebra_glm::Vec2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Yellow,
+}
+
+impl KeyColor {
+    pub const ALL: [KeyColor; 3] = [KeyColor::Red, KeyColor::Blue, KeyColor::Yellow];
+
+    pub fn from_key_glyph(cell: char) -> Option<Self> {
+        match cell {
+            'r' => Some(KeyColor::Red),
+            'b' => Some(KeyColor::Blue),
+            'y' => Some(KeyColor::Yellow),
+            _ => None,
+        }
+    }
+
+    pub fn from_door_glyph(cell: char) -> Option<Self> {
+        match cell {
+            'R' => Some(KeyColor::Red),
+            'B' => Some(KeyColor::Blue),
+            'Y' => Some(KeyColor::Yellow),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self) -> u32 {
+        match self {
+            KeyColor::Red => 0xc82828,
+            KeyColor::Blue => 0x285ad2,
+            KeyColor::Yellow => 0xe6be1e,
+        }
+    }
+
+    pub fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ItemKind {
+    Key(KeyColor),
+}
+
+pub struct Item {
+    pub row: usize,
+    pub col: usize,
+    pub kind: ItemKind,
+}
+
+impl Item {
+    pub fn pos(&self, block_size: usize) -> Vec2 {
+        Vec2::new(
+            (self.col as f32 + 0.5) * block_size as f32,
+            (self.row as f32 + 0.5) * block_size as f32,
+        )
+    }
+}
+
+// Los objetos se colocan en el mapa como letras sobre el piso; al cargarlos
+// la celda vuelve a quedar vacía para que el resto del juego la trate como pasillo.
+pub fn extract_items(maze: &mut [Vec<char>]) -> Vec<Item> {
+    let mut items = Vec::new();
+    for (row, cells) in maze.iter_mut().enumerate() {
+        for (col, cell) in cells.iter_mut().enumerate() {
+            if let Some(color) = KeyColor::from_key_glyph(*cell) {
+                items.push(Item { row, col, kind: ItemKind::Key(color) });
+                *cell = ' ';
+            }
+        }
+    }
+    items
+}
@@ -0,0 +1,20 @@
+pub mod framebuffer;
+pub mod font;
+pub mod maze;
+pub mod player;
+pub mod audio;
+pub mod cast_function;
+pub mod texture;
+pub mod door;
+pub mod item;
+pub mod solver;
+pub mod teleport;
+pub mod sprite;
+pub mod entity;
+pub mod enemy;
+pub mod floor;
+pub mod camera;
+pub mod render;
+pub mod platform;
+pub mod terminal;
+pub mod game;
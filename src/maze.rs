@@ -1,12 +1,42 @@
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 
-pub fn load_maze(filename: &str) -> io::Result<Vec<Vec<char>>> {
+// Un nivel es la grilla del laberinto seguida, tras una línea en blanco, de directivas
+// con lo que no cabe en una celda (entidades, por ejemplo). Cada directiva conserva su
+// número de línea para poder reportar errores.
+pub struct Level {
+    pub maze: Vec<Vec<char>>,
+    pub directives: Vec<(usize, String)>,
+}
+
+pub fn load_level(filename: &str) -> io::Result<Level> {
     let file = File::open(filename)?;
     let reader = BufReader::new(file);
 
-    reader
-        .lines()
-        .map(|line| line.map(|l| l.chars().collect()))
-        .collect()
+    let mut maze = Vec::new();
+    let mut directives = Vec::new();
+    let mut in_grid = true;
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if in_grid {
+            if line.trim().is_empty() {
+                in_grid = false;
+            } else {
+                maze.push(line.chars().collect());
+            }
+            continue;
+        }
+
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            directives.push((number + 1, line.to_string()));
+        }
+    }
+
+    Ok(Level { maze, directives })
+}
+
+pub fn load_maze(filename: &str) -> io::Result<Vec<Vec<char>>> {
+    load_level(filename).map(|level| level.maze)
 }
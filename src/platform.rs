@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+use minifb::{KeyRepeat, MouseMode, Window, WindowOptions};
+use crate::framebuffer::Framebuffer;
+
+// Las teclas que usa el juego, sin depender de cómo las nombre cada backend
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Key {
+    W,
+    A,
+    S,
+    D,
+    E,
+    F,
+    M,
+    R,
+    T,
+    U,
+    // Bajar y subir la escala de render
+    Minus,
+    Plus,
+    Enter,
+    Escape,
+    Num1,
+    Num2,
+    Num3,
+}
+
+impl Key {
+    pub const ALL: [Key; 17] = [
+        Key::W,
+        Key::A,
+        Key::S,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::M,
+        Key::R,
+        Key::T,
+        Key::U,
+        Key::Minus,
+        Key::Plus,
+        Key::Enter,
+        Key::Escape,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+    ];
+}
+
+// Estado de la entrada en un cuadro: las teclas sostenidas, las que se acaban de apretar
+// y la posición horizontal del mouse si está sobre la ventana
+#[derive(Clone, Default, Debug)]
+pub struct Input {
+    pub down: Vec<Key>,
+    pub pressed: Vec<Key>,
+    pub mouse_x: Option<f32>,
+}
+
+impl Input {
+    pub fn is_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    pub fn was_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+}
+
+// Todo lo que el juego necesita de la ventana: mostrar cuadros y leer la entrada
+pub trait Platform {
+    fn is_open(&self) -> bool;
+    // Se llama una vez por cuadro, antes de consultar teclas
+    fn poll_input(&mut self) -> Input;
+    // Tamaño en pixeles en el que se presentan los cuadros
+    fn size(&self) -> (usize, usize);
+    fn present(&mut self, framebuffer: &Framebuffer) -> Result<(), String>;
+    fn set_title(&mut self, title: &str);
+    fn set_cursor_visible(&mut self, visible: bool);
+}
+
+pub struct MinifbPlatform {
+    window: Window,
+}
+
+impl MinifbPlatform {
+    pub fn new(title: &str, width: usize, height: usize) -> Result<Self, String> {
+        let options = WindowOptions { resize: true, ..WindowOptions::default() };
+        let mut window =
+            Window::new(title, width, height, options).map_err(|e| e.to_string())?;
+        window.set_position(0, 0);
+        window.update();
+        Ok(Self { window })
+    }
+
+    fn minifb_key(key: Key) -> minifb::Key {
+        match key {
+            Key::W => minifb::Key::W,
+            Key::A => minifb::Key::A,
+            Key::S => minifb::Key::S,
+            Key::D => minifb::Key::D,
+            Key::E => minifb::Key::E,
+            Key::F => minifb::Key::F,
+            Key::M => minifb::Key::M,
+            Key::R => minifb::Key::R,
+            Key::T => minifb::Key::T,
+            Key::U => minifb::Key::U,
+            Key::Minus => minifb::Key::Minus,
+            Key::Plus => minifb::Key::Equal,
+            Key::Enter => minifb::Key::Enter,
+            Key::Escape => minifb::Key::Escape,
+            Key::Num1 => minifb::Key::Key1,
+            Key::Num2 => minifb::Key::Key2,
+            Key::Num3 => minifb::Key::Key3,
+        }
+    }
+}
+
+impl Platform for MinifbPlatform {
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    // minifb lee los eventos al presentar cada cuadro; aquí solo se consulta lo que dejó
+    fn poll_input(&mut self) -> Input {
+        let window = &self.window;
+        let keys = Key::ALL.iter().copied();
+        Input {
+            down: keys.clone().filter(|&key| window.is_key_down(Self::minifb_key(key))).collect(),
+            pressed: keys
+                .filter(|&key| window.is_key_pressed(Self::minifb_key(key), KeyRepeat::No))
+                .collect(),
+            mouse_x: window.get_mouse_pos(MouseMode::Clamp).map(|(x, _)| x),
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        let (width, height) = self.window.get_size();
+        (width.max(1), height.max(1))
+    }
+
+    fn present(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        self.window
+            .update_with_buffer(&framebuffer.buffer, framebuffer.width, framebuffer.height)
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.window.set_cursor_visibility(visible);
+    }
+}
+
+// Backend en memoria: reproduce una lista de cuadros de teclas sostenidas y guarda una
+// copia de cada cuadro presentado. Se cierra cuando se acaba el guion.
+#[derive(Default)]
+pub struct ScriptedPlatform {
+    script: VecDeque<(Vec<Key>, Option<f32>)>,
+    previous: Vec<Key>,
+    pub frames: Vec<Vec<u32>>,
+    pub size: (usize, usize),
+    pub title: String,
+    pub cursor_visible: bool,
+}
+
+impl ScriptedPlatform {
+    pub fn new(script: impl IntoIterator<Item = Vec<Key>>) -> Self {
+        Self {
+            script: script.into_iter().map(|keys| (keys, None)).collect(),
+            size: (1200, 720),
+            cursor_visible: true,
+            ..Self::default()
+        }
+    }
+
+    // Agrega `frames` cuadros con las mismas teclas sostenidas
+    pub fn hold(&mut self, keys: &[Key], frames: usize) {
+        for _ in 0..frames {
+            self.script.push_back((keys.to_vec(), None));
+        }
+    }
+
+    // Agrega un cuadro con el mouse en `x`
+    pub fn move_mouse(&mut self, x: f32) {
+        self.script.push_back((Vec::new(), Some(x)));
+    }
+}
+
+impl Platform for ScriptedPlatform {
+    fn is_open(&self) -> bool {
+        !self.script.is_empty()
+    }
+
+    fn poll_input(&mut self) -> Input {
+        let (down, mouse_x) = self.script.pop_front().unwrap_or_default();
+        let pressed = down.iter().copied().filter(|key| !self.previous.contains(key)).collect();
+        self.previous = down.clone();
+        Input { down, pressed, mouse_x }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn present(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        self.frames.push(framebuffer.buffer.clone());
+        Ok(())
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+    }
+}
@@ -1,6 +1,13 @@
-use minifb::{Key, Window};
 use nalgebra_glm::Vec2;
 use crate::audio::AudioPlayer;
+use crate::door::{is_open_cell, use_door, Door};
+use crate::floor::{floor_under, FloorTile};
+use crate::item::KeyColor;
+use crate::platform::{Input, Key};
+
+pub const MAX_HEALTH: u32 = 100;
+// Segundos sin recibir daño después de cada golpe
+const INVULNERABLE_SECONDS: f32 = 1.2;
 
 pub struct Player {
     pub pos: Vec2,
@@ -8,6 +15,10 @@ pub struct Player {
     pub fov: f32,
     pub velocity: Vec2,
     pub previous_mouse_pos: Vec2,
+    pub keys: Vec<KeyColor>,
+    pub health: u32,
+    // Segundos que le quedan de invulnerabilidad
+    pub invulnerable: f32,
 }
 
 impl Player {
@@ -18,58 +29,99 @@ impl Player {
             fov,
             velocity: Vec2::new(0.0, 0.0),
             previous_mouse_pos: Vec2::new(0.0, 0.0),
+            keys: Vec::new(),
+            health: MAX_HEALTH,
+            invulnerable: 0.0,
+        }
+    }
+
+    // Devuelve true si el golpe se aplicó; durante la invulnerabilidad se ignora
+    pub fn damage(&mut self, amount: u32) -> bool {
+        if amount == 0 || self.invulnerable > 0.0 || self.is_dead() {
+            return false;
         }
+        self.health = self.health.saturating_sub(amount);
+        self.invulnerable = INVULNERABLE_SECONDS;
+        true
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health == 0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
     }
 
-    pub fn can_move_to(&self, new_pos: Vec2, maze: &[Vec<char>], block_size: usize) -> bool {
+    pub fn update(&mut self, dt: f32) {
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
+    }
+
+    pub fn has_key(&self, color: KeyColor) -> bool {
+        self.keys.contains(&color)
+    }
+
+    pub fn can_move_to(
+        &self,
+        new_pos: Vec2,
+        maze: &[Vec<char>],
+        doors: &[Door],
+        block_size: usize,
+    ) -> bool {
         let row = (new_pos.y / block_size as f32).floor() as usize;
         let col = (new_pos.x / block_size as f32).floor() as usize;
 
-        maze.get(row)
-            .and_then(|r| r.get(col))
-            .map_or(false, |&cell| cell == ' ')
+        is_open_cell(maze, doors, row, col)
     }
 }
 
 pub fn process_events(
-    window: &Window,
+    input: &Input,
     player: &mut Player,
     maze: &[Vec<char>],
+    floor: &[Vec<FloorTile>],
+    doors: &mut [Door],
     block_size: usize,
     audio_player: &AudioPlayer,
 ) {
     const MOVE_SPEED: f32 = 4.0;
-    const ROTATION_SPEED: f32 = 3.14 / 80.0;
+    const ROTATION_SPEED: f32 = std::f32::consts::PI / 80.0;
 
+    let tile = floor_under(floor, player, block_size);
+    let move_speed = MOVE_SPEED * tile.speed_factor();
     let mut moved = false;
     let mut new_pos = player.pos;
 
-    if let Some((mouse_x, _)) = window.get_mouse_pos(minifb::MouseMode::Clamp) {
-        let delta_x = mouse_x as f32 - player.previous_mouse_pos.x;
+    if let Some(mouse_x) = input.mouse_x {
+        let delta_x = mouse_x - player.previous_mouse_pos.x;
         if delta_x.abs() > 0.1 {
             player.a += delta_x.signum() * ROTATION_SPEED;
         }
-        player.previous_mouse_pos.x = mouse_x as f32;
+        player.previous_mouse_pos.x = mouse_x;
     }
 
-    if window.is_key_down(Key::A) {
+    if input.is_down(Key::A) {
         player.a -= ROTATION_SPEED;
     }
-    if window.is_key_down(Key::D) {
+    if input.is_down(Key::D) {
         player.a += ROTATION_SPEED;
     }
-    if window.is_key_down(Key::W) {
-        new_pos.x += MOVE_SPEED * player.a.cos();
-        new_pos.y += MOVE_SPEED * player.a.sin();
+    if input.is_down(Key::W) {
+        new_pos.x += move_speed * player.a.cos();
+        new_pos.y += move_speed * player.a.sin();
         moved = true;
     }
-    if window.is_key_down(Key::S) {
-        new_pos.x -= MOVE_SPEED * player.a.cos();
-        new_pos.y -= MOVE_SPEED * player.a.sin();
+    if input.is_down(Key::S) {
+        new_pos.x -= move_speed * player.a.cos();
+        new_pos.y -= move_speed * player.a.sin();
         moved = true;
     }
 
-    if moved && player.can_move_to(new_pos, maze, block_size) {
+    if input.was_pressed(Key::E) {
+        use_door(doors, player, block_size);
+    }
+
+    if moved && player.can_move_to(new_pos, maze, doors, block_size) {
         if player.pos != new_pos {
             player.pos = new_pos;
             if !audio_player.is_playing() {
@@ -79,4 +131,10 @@ pub fn process_events(
     } else {
         audio_player.pause();
     }
+
+    // Las cintas arrastran al jugador aunque no camine, sin meterlo en las paredes
+    let pushed = player.pos + tile.push();
+    if pushed != player.pos && player.can_move_to(pushed, maze, doors, block_size) {
+        player.pos = pushed;
+    }
 }
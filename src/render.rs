@@ -0,0 +1,297 @@
+use std::thread;
+use nalgebra_glm::Vec2;
+use crate::camera::{Camera, EYE_HEIGHT, WALL_HEIGHT};
+use crate::cast_function::{cast_ray_layers, Face};
+use crate::door::Door;
+use crate::floor::{floor_at, FloorTile};
+use crate::framebuffer::Framebuffer;
+use crate::item::KeyColor;
+use crate::player::Player;
+use crate::texture::{Filter, Texture, TextureRegistry};
+
+#[derive(Clone, Copy)]
+pub struct RenderOptions {
+    pub filter: Filter,
+    // Bloques hasta donde se ve antes de que todo se funda a negro
+    pub view_distance: f32,
+    // Hilos entre los que se reparten las columnas; con 1 todo se dibuja en el hilo actual
+    pub threads: usize,
+}
+
+// Todo lo que hace falta para dibujar la vista 3D; solo se lee, así que los hilos lo
+// comparten sin copiarlo
+pub struct Scene<'a> {
+    pub maze: &'a [Vec<char>],
+    pub floor: &'a [Vec<FloorTile>],
+    pub doors: &'a [Door],
+    pub textures: &'a TextureRegistry,
+    pub placeholder: &'a Texture,
+    pub block_size: usize,
+}
+
+impl Scene<'_> {
+    fn wall_texture(&self, cell: char, face: Face) -> &Texture {
+        self.textures.get(cell, face).unwrap_or(self.placeholder)
+    }
+}
+
+pub fn tint(color: u32, tint: u32, amount: f32) -> u32 {
+    let mix = |shift: u32| {
+        let a = ((color >> shift) & 0xff) as f32;
+        let b = ((tint >> shift) & 0xff) as f32;
+        ((a + (b - a) * amount) as u32) << shift
+    };
+    mix(16) | mix(8) | mix(0)
+}
+
+// Oscurece un color según qué tan lejos está respecto a la distancia de visión,
+// conservando su alfa
+pub fn fog(color: u32, distance: f32, view_distance: f32, block_size: usize) -> u32 {
+    let amount = distance / (view_distance * block_size as f32);
+    if amount <= 0.0 {
+        return color;
+    }
+    (color & 0xff000000) | tint(color, 0x000000, amount.min(1.0))
+}
+
+// Una franja de columnas contiguas que un hilo dibuja por su cuenta antes de copiarla
+// al framebuffer
+struct Band {
+    first_column: usize,
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+impl Band {
+    fn put(&mut self, x: usize, y: usize, color: u32) {
+        if y < self.height {
+            self.pixels[y * self.width + x - self.first_column] = color & 0xffffff;
+        }
+    }
+}
+
+// Una celda más alta que una pared normal
+struct TallCell {
+    min: Vec2,
+    max: Vec2,
+    height: f32,
+}
+
+// Si el rayo que sale de `origin` hacia `dir` cruza el rectángulo [min, max]
+fn ray_crosses(origin: Vec2, dir: Vec2, min: Vec2, max: Vec2) -> bool {
+    let slab = |origin: f32, dir: f32, min: f32, max: f32| {
+        let (t1, t2) = ((min - origin) / dir, (max - origin) / dir);
+        (t1.min(t2), t1.max(t2))
+    };
+    let (near_x, far_x) = slab(origin.x, dir.x, min.x, max.x);
+    let (near_y, far_y) = slab(origin.y, dir.y, min.y, max.y);
+    near_x.max(near_y).max(0.0) <= far_x.min(far_y)
+}
+
+// Dibuja techo, paredes y piso. Las columnas se reparten en franjas entre
+// `options.threads` hilos; los sprites se componen después, sobre el framebuffer ya
+// terminado. `z_buffer` recibe la distancia de la pared que se ve en cada pixel, o
+// infinito donde se ve techo o piso; va columna por columna (`x * alto + y`) para que
+// cada hilo escriba en su propio tramo.
+pub fn render_view(
+    framebuffer: &mut Framebuffer,
+    scene: &Scene,
+    player: &Player,
+    options: RenderOptions,
+    z_buffer: &mut [f32],
+) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    if width == 0 || height == 0 {
+        return;
+    }
+    let band_width = width.div_ceil(options.threads.clamp(1, width));
+    let camera = Camera::new(player, width, height, scene.block_size);
+
+    // El techo y el piso normal son de un solo color por fila, con niebla según su distancia
+    let row_colors: Vec<u32> = (0..height)
+        .map(|y| {
+            let base = if y < height / 2 { 0x2c9473 } else { 0x56615d };
+            fog(base, camera.row_distance(y), options.view_distance, scene.block_size)
+        })
+        .collect();
+
+    // Pasada una pared normal, un rayo solo puede ver algo más si cruza una de estas
+    let block = scene.block_size as f32;
+    let tall_cells: Vec<TallCell> = scene
+        .maze
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| cells.iter().enumerate().map(move |(col, &cell)| (row, col, cell)))
+        .filter_map(|(row, col, cell)| {
+            let height = scene.textures.height(cell);
+            (cell != ' ' && height > 1.0).then(|| {
+                let min = Vec2::new(col as f32 * block, row as f32 * block);
+                TallCell { min, max: min + Vec2::new(block, block), height }
+            })
+        })
+        .collect();
+
+    let mut bands: Vec<Band> = (0..width)
+        .step_by(band_width)
+        .map(|first_column| {
+            let columns = band_width.min(width - first_column);
+            Band {
+                first_column,
+                width: columns,
+                height,
+                pixels: vec![0; columns * height],
+            }
+        })
+        .collect();
+
+    thread::scope(|scope| {
+        let depths = z_buffer[..width * height].chunks_mut(band_width * height);
+        let mut work = bands.iter_mut().zip(depths);
+        // La última franja la dibuja el hilo actual en vez de quedarse esperando
+        let last = work.next_back();
+        for (band, depths) in work {
+            let (row_colors, tall_cells) = (&row_colors, &tall_cells);
+            scope.spawn(move || {
+                render_band(band, depths, scene, &camera, options, row_colors, tall_cells)
+            });
+        }
+        if let Some((band, depths)) = last {
+            render_band(band, depths, scene, &camera, options, &row_colors, &tall_cells);
+        }
+    });
+
+    for band in &bands {
+        for (y, row) in band.pixels.chunks(band.width).enumerate() {
+            let start = y * width + band.first_column;
+            framebuffer.buffer[start..start + band.width].copy_from_slice(row);
+        }
+    }
+}
+
+fn render_band(
+    band: &mut Band,
+    depths: &mut [f32],
+    scene: &Scene,
+    camera: &Camera,
+    options: RenderOptions,
+    row_colors: &[u32],
+    tall_cells: &[TallCell],
+) {
+    for (y, row) in band.pixels.chunks_mut(band.width).enumerate() {
+        row.fill(row_colors[y]);
+    }
+
+    let hh = camera.horizon();
+    let height = band.height as f32;
+    // Filas [desde, hasta) que ocupa cada pared dibujada en la columna actual
+    let mut walls: Vec<(usize, usize)> = Vec::new();
+
+    depths.fill(f32::INFINITY);
+    for (offset, column_depths) in depths.chunks_mut(band.height).enumerate() {
+        let i = band.first_column + offset;
+        let a = camera.ray_angle(i);
+        let correction = (a - camera.angle).cos();
+        let direction = Vec2::new(a.cos(), a.sin());
+        let tallest = tall_cells
+            .iter()
+            .filter(|cell| ray_crosses(camera.pos, direction, cell.min, cell.max))
+            .fold(WALL_HEIGHT, |tallest, cell| tallest.max(WALL_HEIGHT * cell.height));
+        // Las filas por encima de `clip` siguen libres: solo ahí puede asomar una pared más
+        // lejana que las ya dibujadas
+        let mut clip = height;
+        // Fila donde empieza el piso: la base de la pared más lejana que se alcanzó a ver
+        let mut floor_start = hh;
+        walls.clear();
+
+        cast_ray_layers(scene.maze, scene.doors, camera.pos, a, scene.block_size, |intersect| {
+            let distance = (intersect.distance * correction).max(1.0);
+            let wall_height = WALL_HEIGHT * scene.textures.height(intersect.impact);
+            let top = camera.screen_y(wall_height, distance);
+            let bottom = camera.screen_y(0.0, distance);
+            floor_start = bottom;
+
+            // La textura cubre la pared completa aunque no quepa en pantalla, así que de
+            // cerca se ve solo su franja central, sin aplastarse
+            let column_height = bottom - top;
+            let first = top.clamp(0.0, height) as usize;
+            let last = bottom.min(clip).clamp(0.0, height) as usize;
+            if first < last {
+                let texture = scene.wall_texture(intersect.impact, intersect.face);
+                let level = texture.mip_level(column_height);
+                let strip = texture.strip(intersect.tx, level, options.filter);
+                let key = KeyColor::from_door_glyph(intersect.impact);
+                let v_start = (first as f32 + 0.5 - top) / column_height;
+                strip.sample_run(v_start, 1.0 / column_height, last - first, |k, mut color| {
+                    if let Some(key) = key {
+                        color = tint(color, key.color(), 0.45);
+                    }
+                    let color = fog(color, distance, options.view_distance, scene.block_size);
+                    band.put(i, first + k, color);
+                    column_depths[first + k] = distance;
+                });
+                walls.push((first, last));
+            }
+            clip = clip.min(top);
+
+            // Más lejos, lo más alto que puede llegar a verse es la pared más alta que cruza
+            // este rayo puesta a esta misma distancia, o el horizonte si queda por debajo de
+            // los ojos
+            let highest = if tallest > EYE_HEIGHT {
+                camera.screen_y(tallest, distance)
+            } else {
+                hh
+            };
+            clip > 0.0 && highest < clip
+        });
+
+        let first_row = floor_start.clamp(hh, height) as usize;
+        floor_column(band, scene, camera, i, first_row, &walls, options);
+    }
+}
+
+// Proyecta hacia el piso cada pixel bajo el horizonte que no tapa ninguna pared, también
+// los que se ven por encima de una pared baja, para dibujar las celdas especiales con su
+// textura; el piso normal ya quedó pintado de un solo color.
+fn floor_column(
+    band: &mut Band,
+    scene: &Scene,
+    camera: &Camera,
+    column: usize,
+    first_row: usize,
+    walls: &[(usize, usize)],
+    options: RenderOptions,
+) {
+    let a = camera.ray_angle(column);
+    let (sin_a, cos_a) = a.sin_cos();
+    let correction = (a - camera.angle).cos();
+    let block = scene.block_size as f32;
+
+    for y in first_row..band.height {
+        if walls.iter().any(|&(first, last)| (first..last).contains(&y)) {
+            continue;
+        }
+        let perpendicular = camera.row_distance(y);
+        let along_ray = perpendicular / correction;
+        let world_x = camera.pos.x + cos_a * along_ray;
+        let world_y = camera.pos.y + sin_a * along_ray;
+        if world_x < 0.0 || world_y < 0.0 {
+            continue;
+        }
+
+        let tile = floor_at(scene.floor, (world_y / block) as usize, (world_x / block) as usize);
+        let Some(texture) = tile.glyph().and_then(|glyph| scene.textures.floor(glyph)) else {
+            continue;
+        };
+
+        let level = texture.mip_level(camera.projected_size(1.0, perpendicular));
+        let color = texture.sample(
+            (world_x / block).fract(),
+            (world_y / block).fract(),
+            level,
+            options.filter,
+        );
+        band.put(column, y, fog(color, perpendicular, options.view_distance, scene.block_size));
+    }
+}
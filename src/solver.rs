@@ -0,0 +1,235 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use crate::door::is_door;
+use crate::item::{Item, ItemKind, KeyColor};
+use crate::teleport::{teleporter_at, Teleporter};
+
+const NEIGHBOURS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+fn cell_at(maze: &[Vec<char>], row: usize, col: usize) -> Option<char> {
+    maze.get(row).and_then(|r| r.get(col)).copied()
+}
+
+fn step(row: usize, col: usize, (dr, dc): (isize, isize)) -> Option<(usize, usize)> {
+    Some((row.checked_add_signed(dr)?, col.checked_add_signed(dc)?))
+}
+
+// Se llega a la estación al entrar a cualquier celda vecina; el juego y el validador usan
+// esta misma regla
+pub fn is_goal_adjacent(maze: &[Vec<char>], row: usize, col: usize) -> bool {
+    NEIGHBOURS.iter().any(|&offset| {
+        step(row, col, offset).and_then(|(r, c)| cell_at(maze, r, c)) == Some('g')
+    })
+}
+
+// Se puede pisar la celda con las llaves que se llevan (una máscara de bits por color)
+fn is_walkable(cell: char, keys: u8) -> bool {
+    if cell == ' ' {
+        return true;
+    }
+    if !is_door(cell) {
+        return false;
+    }
+    KeyColor::from_door_glyph(cell).map_or(true, |color| keys & color.bit() != 0)
+}
+
+fn keys_at(items: &[Item], row: usize, col: usize) -> u8 {
+    items
+        .iter()
+        .filter(|item| item.row == row && item.col == col)
+        .map(|item| match item.kind {
+            ItemKind::Key(color) => color.bit(),
+        })
+        .fold(0, |acc, bit| acc | bit)
+}
+
+type State = (usize, usize, u8);
+
+// Recorre el laberinto a lo ancho desde el punto de inicio llevando la cuenta de las
+// llaves recogidas, de modo que una puerta solo se cruza si su llave se alcanzó antes.
+// Pisar una plataforma lleva directo a su pareja. Devuelve la ruta más corta en celdas.
+pub fn solve_level(
+    maze: &[Vec<char>],
+    items: &[Item],
+    teleporters: &[Teleporter],
+    spawn: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    let (spawn_row, spawn_col) = spawn;
+    let start = (spawn_row, spawn_col, keys_at(items, spawn_row, spawn_col));
+    let mut parents: HashMap<State, Option<State>> = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(state) = queue.pop_front() {
+        let (row, col, keys) = state;
+        if is_goal_adjacent(maze, row, col) {
+            let mut path = vec![(row, col)];
+            let mut current = state;
+            while let Some(Some(parent)) = parents.get(&current) {
+                path.push((parent.0, parent.1));
+                current = *parent;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for &offset in &NEIGHBOURS {
+            let Some((mut r, mut c)) = step(row, col, offset) else {
+                continue;
+            };
+            let Some(cell) = cell_at(maze, r, c) else {
+                continue;
+            };
+            if !is_walkable(cell, keys) {
+                continue;
+            }
+            if let Some(pad) = teleporter_at(teleporters, r, c) {
+                (r, c) = pad.target;
+            }
+
+            let next = (r, c, keys | keys_at(items, r, c));
+            if let Entry::Vacant(entry) = parents.entry(next) {
+                entry.insert(Some(state));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+pub fn validate_level(
+    maze: &[Vec<char>],
+    items: &[Item],
+    teleporters: &[Teleporter],
+    spawn: (usize, usize),
+) -> Result<Vec<(usize, usize)>, String> {
+    let (spawn_row, spawn_col) = spawn;
+    if cell_at(maze, spawn_row, spawn_col) != Some(' ') {
+        return Err(format!("spawn cell ({}, {}) is not empty floor", spawn_row, spawn_col));
+    }
+
+    solve_level(maze, items, teleporters, spawn).ok_or_else(|| {
+        "the goal cannot be reached from the spawn with the keys placed in the level".to_string()
+    })
+}
+
+// A* sobre la grilla con la distancia Manhattan como estimación; `passable` decide qué
+// celdas se pueden pisar. Devuelve las celdas a recorrer después de `start`, terminando
+// en `goal`, o None si no hay camino.
+pub fn find_path(
+    maze: &[Vec<char>],
+    start: (usize, usize),
+    goal: (usize, usize),
+    passable: impl Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+    let estimate = |(row, col): (usize, usize)| row.abs_diff(goal.0) + col.abs_diff(goal.1);
+
+    let mut parents: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut costs: HashMap<(usize, usize), usize> = HashMap::from([(start, 0)]);
+    let mut open = BinaryHeap::from([Reverse((estimate(start), 0, start))]);
+
+    while let Some(Reverse((_, cost, cell))) = open.pop() {
+        if cell == goal {
+            let mut path = vec![cell];
+            let mut current = cell;
+            while let Some(&parent) = parents.get(&current) {
+                if parent == start {
+                    break;
+                }
+                path.push(parent);
+                current = parent;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if costs.get(&cell).is_some_and(|&best| cost > best) {
+            continue;
+        }
+
+        for &offset in &NEIGHBOURS {
+            let Some(next) = step(cell.0, cell.1, offset) else {
+                continue;
+            };
+            if cell_at(maze, next.0, next.1).is_none() || !passable(next.0, next.1) {
+                continue;
+            }
+            let next_cost = cost + 1;
+            if costs.get(&next).map_or(true, |&best| next_cost < best) {
+                costs.insert(next, next_cost);
+                parents.insert(next, cell);
+                open.push(Reverse((next_cost + estimate(next), next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::extract_items;
+    use crate::teleport::extract_teleporters;
+
+    const SPAWN: (usize, usize) = (1, 1);
+
+    // Lee el nivel igual que el juego: llaves y plataformas salen del mapa y quedan como piso
+    fn level(rows: &[&str]) -> (Vec<Vec<char>>, Vec<Item>, Vec<Teleporter>) {
+        let mut maze: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+        let items = extract_items(&mut maze);
+        let teleporters = extract_teleporters(&mut maze).expect("teleporters come in pairs");
+        (maze, items, teleporters)
+    }
+
+    #[test]
+    fn a_key_behind_its_own_door_is_rejected() {
+        let (maze, items, teleporters) = level(&["+++++++", "+  Rr +", "+++++g+"]);
+        assert!(validate_level(&maze, &items, &teleporters, SPAWN).is_err());
+    }
+
+    #[test]
+    fn a_key_reached_before_its_door_opens_the_way() {
+        let (maze, items, teleporters) = level(&["+++++++", "+ rR  +", "+++++g+"]);
+        let path = validate_level(&maze, &items, &teleporters, SPAWN).expect("level is solvable");
+        assert!(path.contains(&(1, 2)));
+        assert_eq!(path.last(), Some(&(1, 5)));
+    }
+
+    #[test]
+    fn a_wall_can_only_be_crossed_through_a_teleporter_pair() {
+        let (maze, items, teleporters) = level(&["+++++++", "+ 1+1 +", "+++++g+"]);
+        let path = validate_level(&maze, &items, &teleporters, SPAWN).expect("level is solvable");
+        assert_eq!(path, vec![(1, 1), (1, 4), (1, 5)]);
+        assert!(validate_level(&maze, &items, &[], SPAWN).is_err());
+    }
+
+    fn floor(maze: &[Vec<char>]) -> impl Fn(usize, usize) -> bool + '_ {
+        move |row, col| maze[row][col] == ' '
+    }
+
+    #[test]
+    fn find_path_walks_around_a_wall() {
+        let (maze, _, _) = level(&["+++++", "+   +", "+ + +", "+   +", "+++++"]);
+        let path = find_path(&maze, (2, 1), (2, 3), floor(&maze)).expect("path exists");
+
+        assert_eq!(path.len(), 4);
+        assert!(!path.contains(&(2, 1)));
+        assert_eq!(path.last(), Some(&(2, 3)));
+        let mut previous = (2, 1);
+        for &(row, col) in &path {
+            assert_eq!(row.abs_diff(previous.0) + col.abs_diff(previous.1), 1);
+            assert_eq!(maze[row][col], ' ');
+            previous = (row, col);
+        }
+    }
+
+    #[test]
+    fn find_path_gives_up_on_a_walled_off_goal() {
+        let (maze, _, _) = level(&["+++++++", "+  +  +", "+++++++"]);
+        assert_eq!(find_path(&maze, (1, 1), (1, 5), floor(&maze)), None);
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use crate::texture::{load_texture, Filter, Texture, TextureError};
+
+// Un rectángulo de una textura que se dibuja como sprite; las coordenadas que recibe
+// `sample` son relativas al cuadro y se trasladan a la hoja completa.
+#[derive(Clone, Copy)]
+pub struct SpriteFrame<'a> {
+    texture: &'a Texture,
+    u0: f32,
+    v0: f32,
+    du: f32,
+    dv: f32,
+}
+
+impl<'a> SpriteFrame<'a> {
+    pub fn whole(texture: &'a Texture) -> Self {
+        Self { texture, u0: 0.0, v0: 0.0, du: 1.0, dv: 1.0 }
+    }
+
+    pub fn mip_level(&self, screen_size: f32) -> usize {
+        self.texture.mip_level(screen_size / self.du.min(self.dv))
+    }
+
+    pub fn sample(&self, u: f32, v: f32, level: usize, filter: Filter) -> u32 {
+        let u = self.u0 + u.clamp(0.0, 0.999) * self.du;
+        let v = self.v0 + v.clamp(0.0, 0.999) * self.dv;
+        self.texture.sample(u, v, level, filter)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Animation {
+    first: usize,
+    count: usize,
+    fps: f32,
+}
+
+pub struct SpriteSheet {
+    texture: Arc<Texture>,
+    columns: usize,
+    rows: usize,
+    // Vistas de la entidad desde distintos ángulos; 1 si el sprite se ve igual desde todos
+    rotations: usize,
+    animations: HashMap<String, Animation>,
+}
+
+impl SpriteSheet {
+    // Una sola imagen sin animaciones, útil como reemplazo si falla la hoja
+    pub fn single(texture: Arc<Texture>) -> Self {
+        Self { texture, columns: 1, rows: 1, rotations: 1, animations: HashMap::new() }
+    }
+
+    // El archivo de la hoja describe la imagen, la grilla de cuadros y las animaciones:
+    //   image assets/alien_sheet.png
+    //   grid 4 3
+    //   rotations 8
+    //   animation idle 0 4 4
+    // donde cada animación indica su primer cuadro, cuántos cuadros tiene y sus fps.
+    // Con varias rotaciones la grilla se parte en tantos bloques iguales, uno por vista:
+    // el bloque 0 es la entidad de frente a la cámara y los siguientes avanzan en el
+    // sentido en que crecen los ángulos. Los cuadros de las animaciones cuentan dentro
+    // de cada bloque.
+    pub fn load(sheet_path: &str) -> Result<Self, TextureError> {
+        let file = File::open(sheet_path)
+            .map_err(|source| TextureError::Open { path: sheet_path.to_string(), source })?;
+
+        let mut texture = None;
+        let mut grid = (1, 1);
+        let mut rotations = 1;
+        let mut animations = HashMap::new();
+
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line =
+                line.map_err(|source| TextureError::Open { path: sheet_path.to_string(), source })?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |message: &str| TextureError::Manifest {
+                path: sheet_path.to_string(),
+                line: number + 1,
+                message: message.to_string(),
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            match fields.as_slice() {
+                ["image", path] => texture = Some(load_texture(path)),
+                ["grid", columns, rows] => {
+                    let columns: usize = columns.parse().map_err(|_| invalid("invalid columns"))?;
+                    let rows: usize = rows.parse().map_err(|_| invalid("invalid rows"))?;
+                    if columns == 0 || rows == 0 {
+                        return Err(invalid("grid must have at least one frame"));
+                    }
+                    grid = (columns, rows);
+                }
+                ["rotations", count] => {
+                    rotations = count.parse().map_err(|_| invalid("invalid rotation count"))?;
+                    if rotations == 0 {
+                        return Err(invalid("rotations must be at least 1"));
+                    }
+                }
+                ["animation", name, first, count, fps] => {
+                    let animation = Animation {
+                        first: first.parse().map_err(|_| invalid("invalid first frame"))?,
+                        count: count.parse().map_err(|_| invalid("invalid frame count"))?,
+                        fps: fps.parse().map_err(|_| invalid("invalid fps"))?,
+                    };
+                    if animation.count == 0 {
+                        return Err(invalid("animation needs at least one frame"));
+                    }
+                    animations.insert(name.to_string(), animation);
+                }
+                _ => return Err(invalid("expected image, grid, rotations or animation")),
+            }
+        }
+
+        let texture = texture.ok_or_else(|| TextureError::Manifest {
+            path: sheet_path.to_string(),
+            line: 0,
+            message: "missing image".to_string(),
+        })?;
+        let (columns, rows) = grid;
+        if (columns * rows) % rotations != 0 {
+            return Err(TextureError::Manifest {
+                path: sheet_path.to_string(),
+                line: 0,
+                message: format!(
+                    "{} frames cannot be split into {} rotations",
+                    columns * rows,
+                    rotations
+                ),
+            });
+        }
+        let frames_per_rotation = columns * rows / rotations;
+
+        for (name, animation) in &animations {
+            if animation.first + animation.count > frames_per_rotation {
+                return Err(TextureError::Manifest {
+                    path: sheet_path.to_string(),
+                    line: 0,
+                    message: format!("animation '{}' runs past the last frame", name),
+                });
+            }
+        }
+
+        Ok(Self { texture, columns, rows, rotations, animations })
+    }
+
+    // Qué vista usar según el ángulo desde la entidad hacia la cámara, medido respecto
+    // a hacia dónde mira la entidad; se redondea a la rotación más cercana.
+    pub fn rotation(&self, view_angle: f32) -> usize {
+        let step = std::f32::consts::TAU / self.rotations as f32;
+        (view_angle.rem_euclid(std::f32::consts::TAU) / step).round() as usize % self.rotations
+    }
+
+    pub fn frame(&self, index: usize, rotation: usize) -> SpriteFrame<'_> {
+        let frames_per_rotation = self.columns * self.rows / self.rotations;
+        let index = index % frames_per_rotation + (rotation % self.rotations) * frames_per_rotation;
+        let du = 1.0 / self.columns as f32;
+        let dv = 1.0 / self.rows as f32;
+        SpriteFrame {
+            texture: &self.texture,
+            u0: (index % self.columns) as f32 * du,
+            v0: (index / self.columns) as f32 * dv,
+            du,
+            dv,
+        }
+    }
+
+    // Cuadro de la animación tras `elapsed` segundos; las animaciones se repiten en bucle
+    // y una animación desconocida se queda en el primer cuadro de la hoja.
+    pub fn animation_frame(
+        &self,
+        animation: &str,
+        elapsed: f32,
+        view_angle: f32,
+    ) -> SpriteFrame<'_> {
+        let index = self.animations.get(animation).map_or(0, |animation| {
+            let step = (elapsed * animation.fps).max(0.0) as usize;
+            animation.first + step % animation.count
+        });
+        self.frame(index, self.rotation(view_angle))
+    }
+}
+
+// Estado de animación de una entidad: qué animación corre y desde cuándo
+pub struct Animator {
+    animation: &'static str,
+    elapsed: f32,
+}
+
+impl Animator {
+    pub fn new(animation: &'static str) -> Self {
+        Self { animation, elapsed: 0.0 }
+    }
+
+    // Cambia de animación reiniciando el reloj, salvo que ya sea la actual
+    pub fn play(&mut self, animation: &'static str) {
+        if self.animation != animation {
+            self.animation = animation;
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    pub fn frame<'a>(&self, sheet: &'a SpriteSheet, view_angle: f32) -> SpriteFrame<'a> {
+        sheet.animation_frame(self.animation, self.elapsed, view_angle)
+    }
+}
@@ -0,0 +1,124 @@
+use nalgebra_glm::Vec2;
+use crate::player::Player;
+
+pub struct Teleporter {
+    pub row: usize,
+    pub col: usize,
+    pub target: (usize, usize),
+    // Dirección con la que sale el jugador al llegar a esta plataforma
+    pub heading: f32,
+    occupied: bool,
+}
+
+impl Teleporter {
+    pub fn pos(&self, block_size: usize) -> Vec2 {
+        Vec2::new(
+            (self.col as f32 + 0.5) * block_size as f32,
+            (self.row as f32 + 0.5) * block_size as f32,
+        )
+    }
+}
+
+pub fn is_teleporter(cell: char) -> bool {
+    cell.is_ascii_digit() && cell != '0'
+}
+
+// Apunta hacia la primera celda libre junto a la plataforma para no salir mirando a un muro
+fn exit_heading(maze: &[Vec<char>], row: usize, col: usize) -> f32 {
+    let directions = [
+        (0.0, Some(row), col.checked_add(1)),
+        (std::f32::consts::FRAC_PI_2, row.checked_add(1), Some(col)),
+        (std::f32::consts::PI, Some(row), col.checked_sub(1)),
+        (-std::f32::consts::FRAC_PI_2, row.checked_sub(1), Some(col)),
+    ];
+
+    directions
+        .iter()
+        .find(|(_, r, c)| {
+            r.zip(*c)
+                .and_then(|(r, c)| maze.get(r).and_then(|cells| cells.get(c)))
+                .is_some_and(|&cell| cell == ' ' || is_teleporter(cell))
+        })
+        .map_or(0.0, |(heading, _, _)| *heading)
+}
+
+// Las plataformas se marcan en el mapa con un dígito y se emparejan por ese dígito.
+// Igual que con los objetos, la celda queda como piso después de leerlas.
+pub fn extract_teleporters(maze: &mut [Vec<char>]) -> Result<Vec<Teleporter>, String> {
+    let mut pads: Vec<(char, usize, usize)> = Vec::new();
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            if is_teleporter(cell) {
+                pads.push((cell, row, col));
+            }
+        }
+    }
+
+    let mut teleporters = Vec::new();
+    for &(id, row, col) in &pads {
+        let pair: Vec<_> = pads.iter().filter(|(other, _, _)| *other == id).collect();
+        if pair.len() != 2 {
+            return Err(format!(
+                "teleporter '{}' must appear exactly twice, found {}",
+                id,
+                pair.len()
+            ));
+        }
+        let &&(_, target_row, target_col) = pair
+            .iter()
+            .find(|(_, r, c)| (*r, *c) != (row, col))
+            .expect("teleporter pair has two distinct cells");
+
+        teleporters.push(Teleporter {
+            row,
+            col,
+            target: (target_row, target_col),
+            heading: exit_heading(maze, row, col),
+            occupied: false,
+        });
+    }
+
+    for &(_, row, col) in &pads {
+        maze[row][col] = ' ';
+    }
+
+    Ok(teleporters)
+}
+
+pub fn teleporter_at(teleporters: &[Teleporter], row: usize, col: usize) -> Option<&Teleporter> {
+    teleporters.iter().find(|pad| pad.row == row && pad.col == col)
+}
+
+// Devuelve true si el jugador fue teletransportado en este cuadro. La plataforma de
+// llegada queda ocupada hasta que el jugador se baje de ella, así no rebota de vuelta.
+pub fn try_teleport(
+    teleporters: &mut [Teleporter],
+    player: &mut Player,
+    block_size: usize,
+) -> bool {
+    let row = (player.pos.y / block_size as f32).floor() as usize;
+    let col = (player.pos.x / block_size as f32).floor() as usize;
+
+    for pad in teleporters.iter_mut() {
+        if (pad.row, pad.col) != (row, col) {
+            pad.occupied = false;
+        }
+    }
+
+    let Some(source) = teleporters.iter().position(|pad| (pad.row, pad.col) == (row, col)) else {
+        return false;
+    };
+    if teleporters[source].occupied {
+        return false;
+    }
+
+    let target = teleporters[source].target;
+    let Some(destination) = teleporters.iter_mut().find(|pad| (pad.row, pad.col) == target) else {
+        return false;
+    };
+
+    destination.occupied = true;
+    player.pos = destination.pos(block_size);
+    player.a = destination.heading;
+    true
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, execute, terminal};
+use crate::framebuffer::Framebuffer;
+use crate::platform::{Input, Key, Platform};
+
+// El texto y el HUD se acomodan en pixeles, así que el juego sigue dibujando a este tamaño
+// y `present` lo reduce a las celdas disponibles; para ganar velocidad conviene bajar la
+// escala de render
+const LOGICAL_SIZE: (usize, usize) = (1200, 720);
+
+// Sin eventos de soltar tecla (lo común por SSH) una tecla recién apretada se da por
+// sostenida durante más tiempo que la demora de la repetición automática, que suele ir de
+// 250 a 600 ms; así caminar no se corta antes de que empiecen a llegar las repeticiones.
+// Desde ahí basta con que sigan llegando, y se suelta poco después de la última.
+const HOLD_UNTIL_REPEAT: Duration = Duration::from_millis(500);
+const HOLD_BETWEEN_REPEATS: Duration = Duration::from_millis(100);
+
+// Si la terminal sigue en modo crudo; lo consulta el hook de pánico, que no tiene acceso
+// al TerminalPlatform
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static REPORTS_RELEASE: AtomicBool = AtomicBool::new(false);
+
+// Devuelve la terminal a como estaba; solo la primera llamada hace algo, para que el Drop
+// que corre después de un pánico no mueva el cursor encima del mensaje de error
+fn restore_terminal() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let mut stdout = io::stdout();
+    if REPORTS_RELEASE.load(Ordering::SeqCst) {
+        let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+    }
+    let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+// Dibuja en la terminal con medios bloques ('▀'): cada celda muestra dos pixeles, el de
+// arriba con el color del texto y el de abajo con el del fondo, en color de 24 bits
+pub struct TerminalPlatform {
+    open: bool,
+    columns: usize,
+    rows: usize,
+    // Con el protocolo de teclado extendido la terminal avisa al soltar cada tecla
+    reports_release: bool,
+    // Hasta cuándo se da por sostenida cada tecla si la terminal no avisa al soltarla
+    held: HashMap<Key, Instant>,
+    // Se borra la pantalla solo al empezar y al cambiar de tamaño, para no parpadear
+    needs_clear: bool,
+    output: String,
+}
+
+impl TerminalPlatform {
+    pub fn new() -> io::Result<Self> {
+        let (columns, rows) = terminal::size()?;
+        terminal::enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        // El hook por defecto escribiría el mensaje dentro de la pantalla alternativa, que
+        // desaparece al salir; primero se restaura la terminal y después se imprime
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous_hook(info);
+        }));
+
+        let mut stdout = io::stdout();
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        let reports_release = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if reports_release {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+        REPORTS_RELEASE.store(reports_release, Ordering::SeqCst);
+
+        Ok(Self {
+            open: true,
+            columns: columns as usize,
+            rows: rows as usize,
+            reports_release,
+            held: HashMap::new(),
+            needs_clear: true,
+            output: String::new(),
+        })
+    }
+
+    fn key(code: KeyCode) -> Option<Key> {
+        match code {
+            KeyCode::Char(c) => match c.to_ascii_lowercase() {
+                'w' => Some(Key::W),
+                'a' => Some(Key::A),
+                's' => Some(Key::S),
+                'd' => Some(Key::D),
+                'e' => Some(Key::E),
+                'f' => Some(Key::F),
+                'm' => Some(Key::M),
+                'r' => Some(Key::R),
+                't' => Some(Key::T),
+                'u' => Some(Key::U),
+                '-' => Some(Key::Minus),
+                '+' | '=' => Some(Key::Plus),
+                '1' => Some(Key::Num1),
+                '2' => Some(Key::Num2),
+                '3' => Some(Key::Num3),
+                _ => None,
+            },
+            KeyCode::Up => Some(Key::W),
+            KeyCode::Down => Some(Key::S),
+            KeyCode::Left => Some(Key::A),
+            KeyCode::Right => Some(Key::D),
+            KeyCode::Enter => Some(Key::Enter),
+            KeyCode::Esc => Some(Key::Escape),
+            _ => None,
+        }
+    }
+
+    fn handle_key(&mut self, event: KeyEvent, pressed: &mut Vec<Key>) {
+        if event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open = false;
+            return;
+        }
+        let Some(key) = Self::key(event.code) else {
+            return;
+        };
+        match event.kind {
+            // Sin el protocolo extendido las repeticiones también llegan como Press
+            KeyEventKind::Press if !self.held.contains_key(&key) => {
+                pressed.push(key);
+                self.held.insert(key, Instant::now() + HOLD_UNTIL_REPEAT);
+            }
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                self.held.insert(key, Instant::now() + HOLD_BETWEEN_REPEATS);
+            }
+            KeyEventKind::Release => {
+                self.held.remove(&key);
+            }
+        }
+    }
+}
+
+// Promedia los pixeles del rectángulo [x0, x1) x [y0, y1), que nunca queda vacío
+fn average(framebuffer: &Framebuffer, x0: usize, x1: usize, y0: usize, y1: usize) -> u32 {
+    let (mut r, mut g, mut b) = (0, 0, 0);
+    for row in framebuffer.buffer[y0 * framebuffer.width..y1 * framebuffer.width]
+        .chunks(framebuffer.width)
+    {
+        for &pixel in &row[x0..x1] {
+            r += (pixel >> 16) & 0xff;
+            g += (pixel >> 8) & 0xff;
+            b += pixel & 0xff;
+        }
+    }
+    let count = ((x1 - x0) * (y1 - y0)) as u32;
+    ((r / count) << 16) | ((g / count) << 8) | (b / count)
+}
+
+impl Platform for TerminalPlatform {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn poll_input(&mut self) -> Input {
+        let mut pressed = Vec::new();
+        while self.open && event::poll(Duration::ZERO).unwrap_or(false) {
+            match event::read() {
+                Ok(Event::Key(key)) => self.handle_key(key, &mut pressed),
+                Ok(Event::Resize(columns, rows)) => {
+                    self.columns = columns as usize;
+                    self.rows = rows as usize;
+                    self.needs_clear = true;
+                }
+                Ok(_) => {}
+                Err(_) => self.open = false,
+            }
+        }
+
+        if !self.reports_release {
+            let now = Instant::now();
+            self.held.retain(|_, until| *until > now);
+        }
+        Input { down: self.held.keys().copied().collect(), pressed, mouse_x: None }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        LOGICAL_SIZE
+    }
+
+    // Reduce el cuadro al tamaño de la terminal sin deformarlo y lo centra
+    fn present(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        let pixel_rows = self.rows * 2;
+        let fit = (self.columns as f32 / framebuffer.width as f32)
+            .min(pixel_rows as f32 / framebuffer.height as f32)
+            .min(1.0);
+        let width = ((framebuffer.width as f32 * fit) as usize).max(1);
+        let height = ((framebuffer.height as f32 * fit) as usize / 2).max(1) * 2;
+        let left = (self.columns.saturating_sub(width)) / 2;
+        let top = (pixel_rows.saturating_sub(height)) / 4;
+
+        // Límites en el framebuffer de la columna o fila `i` de `n`
+        let span = |i: usize, n: usize, size: usize| {
+            let start = i * size / n;
+            (start, ((i + 1) * size / n).max(start + 1).min(size))
+        };
+
+        self.output.clear();
+        if std::mem::take(&mut self.needs_clear) {
+            self.output.push_str("\x1b[0m\x1b[2J");
+        }
+        let mut last = None;
+        for row in 0..height / 2 {
+            let _ = write!(self.output, "\x1b[{};{}H", top + row + 1, left + 1);
+            let (upper_top, upper_bottom) = span(row * 2, height, framebuffer.height);
+            let (lower_top, lower_bottom) = span(row * 2 + 1, height, framebuffer.height);
+            for column in 0..width {
+                let (x0, x1) = span(column, width, framebuffer.width);
+                let upper = average(framebuffer, x0, x1, upper_top, upper_bottom);
+                let lower = average(framebuffer, x0, x1, lower_top, lower_bottom);
+                // Solo se repiten los códigos de color cuando cambian
+                if last != Some((upper, lower)) {
+                    let _ = write!(
+                        self.output,
+                        "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+                        upper >> 16,
+                        (upper >> 8) & 0xff,
+                        upper & 0xff,
+                        lower >> 16,
+                        (lower >> 8) & 0xff,
+                        lower & 0xff
+                    );
+                    last = Some((upper, lower));
+                }
+                self.output.push('▀');
+            }
+        }
+
+        let mut stdout = io::stdout().lock();
+        stdout
+            .write_all(self.output.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_title(&mut self, title: &str) {
+        let _ = execute!(io::stdout(), terminal::SetTitle(title));
+    }
+
+    // La terminal no tiene puntero; el cursor de texto queda oculto todo el tiempo
+    fn set_cursor_visible(&mut self, _visible: bool) {}
+}
+
+impl Drop for TerminalPlatform {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
@@ -1,44 +1,419 @@
-use image::{DynamicImage, GenericImageView, ImageReader, Pixel, imageops::resize};
+use image::{DynamicImage, GenericImageView, ImageError, ImageReader, Pixel};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::sync::{Arc, Mutex};
+use crate::cast_function::Face;
 
+const PLACEHOLDER_SIZE: u32 = 128;
+const PLACEHOLDER_CHECKER: u32 = 16;
+
+static LOAD_ERRORS: Mutex<Vec<TextureError>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+pub enum TextureError {
+    Open { path: String, source: io::Error },
+    Decode { path: String, source: ImageError },
+    Manifest { path: String, line: usize, message: String },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Open { path, source } => {
+                write!(f, "{}: cannot open file: {}", path, source)
+            }
+            TextureError::Decode { path, source } => {
+                write!(f, "{}: cannot decode image: {}", path, source)
+            }
+            TextureError::Manifest { path, line, message } => {
+                write!(f, "{}:{}: {}", path, line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureError::Open { source, .. } => Some(source),
+            TextureError::Decode { source, .. } => Some(source),
+            TextureError::Manifest { .. } => None,
+        }
+    }
+}
+
+fn record_error(error: TextureError) {
+    if let Ok(mut errors) = LOAD_ERRORS.lock() {
+        errors.push(error);
+    }
+}
+
+// Devuelve los errores acumulados desde la última llamada
+pub fn take_load_errors() -> Vec<TextureError> {
+    LOAD_ERRORS.lock().map(|mut errors| std::mem::take(&mut *errors)).unwrap_or_default()
+}
+
+// Carga la textura o, si falla, anota el error y devuelve el tablero de reemplazo
+pub fn load_texture(file_path: &str) -> Arc<Texture> {
+    match Texture::load(file_path) {
+        Ok(texture) => Arc::new(texture),
+        Err(e) => {
+            record_error(e);
+            Arc::new(Texture::placeholder())
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+// Texels guardados por columnas en un solo bloque contiguo: el texel (x, y) está en
+// `texels[x * height + y]`, de modo que una franja vertical de pared es un slice.
+struct MipLevel {
+    width: u32,
+    height: u32,
+    texels: Vec<u32>,
+}
+
+impl MipLevel {
+    fn column(&self, x: u32) -> &[u32] {
+        let start = x as usize * self.height as usize;
+        &self.texels[start..start + self.height as usize]
+    }
+
+    // Promedia bloques de 2x2 para obtener el siguiente nivel de la cadena
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width as usize * height as usize);
+
+        for x in 0..width {
+            let x0 = (x * 2).min(self.width - 1);
+            let x1 = (x0 + 1).min(self.width - 1);
+            let (left, right) = (self.column(x0), self.column(x1));
+            for y in 0..height {
+                let y0 = (y * 2).min(self.height - 1) as usize;
+                let y1 = (y0 + 1).min(self.height as usize - 1);
+                texels.push(average([left[y0], right[y0], left[y1], right[y1]]));
+            }
+        }
+
+        MipLevel { width, height, texels }
+    }
+
+    fn strip(&self, u: f32, filter: Filter) -> TextureStrip<'_> {
+        match filter {
+            Filter::Nearest => {
+                let x = ((u * self.width as f32) as u32).min(self.width - 1);
+                let column = self.column(x);
+                TextureStrip { left: column, right: column, weight: 0.0, filter }
+            }
+            Filter::Bilinear => {
+                let fx = (u * self.width as f32 - 0.5).max(0.0);
+                let x0 = (fx as u32).min(self.width - 1);
+                let x1 = (x0 + 1).min(self.width - 1);
+                TextureStrip {
+                    left: self.column(x0),
+                    right: self.column(x1),
+                    weight: fx - x0 as f32,
+                    filter,
+                }
+            }
+        }
+    }
+}
+
+// Una franja vertical de la textura ya resuelta en x: el bucle interno de las paredes
+// solo calcula el índice en y y lee memoria contigua.
+pub struct TextureStrip<'a> {
+    left: &'a [u32],
+    right: &'a [u32],
+    weight: f32,
+    filter: Filter,
+}
+
+impl TextureStrip<'_> {
+    #[inline]
+    fn texel(column: &[u32], y: usize) -> u32 {
+        debug_assert!(y < column.len());
+        // SAFETY: todos los índices se acotan a `len - 1` antes de llegar aquí y ningún
+        // nivel de la cadena tiene columnas vacías.
+        unsafe { *column.get_unchecked(y) }
+    }
+
+    // `v` es la coordenada vertical normalizada; fuera de [0, 1) se satura al borde
+    #[inline]
+    pub fn sample(&self, v: f32) -> u32 {
+        let last = self.left.len() - 1;
+        match self.filter {
+            Filter::Nearest => {
+                let y = ((v * self.left.len() as f32) as usize).min(last);
+                Self::texel(self.left, y)
+            }
+            Filter::Bilinear => {
+                let fy = (v * self.left.len() as f32 - 0.5).max(0.0);
+                let y0 = (fy as usize).min(last);
+                let y1 = (y0 + 1).min(last);
+                let wy = fy - y0 as f32;
+
+                let top =
+                    lerp(Self::texel(self.left, y0), Self::texel(self.right, y0), self.weight);
+                let bottom =
+                    lerp(Self::texel(self.left, y1), Self::texel(self.right, y1), self.weight);
+                lerp(top, bottom, wy)
+            }
+        }
+    }
+
+    // Muestrea `count` texels seguidos empezando en `v_start` y avanzando `v_step` por
+    // pixel, sin la división por pixel que haría llamar a `sample` en un bucle
+    #[inline]
+    pub fn sample_run(
+        &self,
+        v_start: f32,
+        v_step: f32,
+        count: usize,
+        mut put: impl FnMut(usize, u32),
+    ) {
+        match self.filter {
+            Filter::Nearest => {
+                let len = self.left.len() as f32;
+                let last = self.left.len() - 1;
+                let step = v_step * len;
+                let mut pos = v_start * len;
+                for k in 0..count {
+                    put(k, Self::texel(self.left, (pos as usize).min(last)));
+                    pos += step;
+                }
+            }
+            Filter::Bilinear => {
+                for k in 0..count {
+                    put(k, self.sample(v_start + v_step * k as f32));
+                }
+            }
+        }
+    }
+}
+
+fn channel(color: u32, shift: u32) -> u32 {
+    (color >> shift) & 0xff
+}
+
+// Pondera el color por su alfa para que los texels transparentes no tiñan los bordes
+fn average(colors: [u32; 4]) -> u32 {
+    let alpha: u32 = colors.iter().map(|&c| channel(c, 24)).sum();
+    if alpha == 0 {
+        return 0;
+    }
+    let mix = |shift: u32| {
+        let weighted: u32 = colors.iter().map(|&c| channel(c, shift) * channel(c, 24)).sum();
+        (weighted / alpha) << shift
+    };
+    ((alpha / 4) << 24) | mix(16) | mix(8) | mix(0)
+}
+
+// Interpola en punto fijo: rojo con azul y alfa con verde viajan juntos en una palabra
+fn lerp(a: u32, b: u32, t: f32) -> u32 {
+    let w = (t.clamp(0.0, 1.0) * 256.0) as u32;
+    let rb = (((a & 0xff00ff) * (256 - w) + (b & 0xff00ff) * w) >> 8) & 0xff00ff;
+    let ag = ((((a >> 8) & 0xff00ff) * (256 - w) + ((b >> 8) & 0xff00ff) * w) >> 8) & 0xff00ff;
+    rb | (ag << 8)
+}
+
+// Los texels se guardan como 0xAARRGGBB
 pub struct Texture {
     pub width: u32,
     pub height: u32,
-    pub color_array: Vec<Vec<u32>>,
+    // Nivel 0 a la resolución original, cada siguiente a la mitad
+    mips: Vec<MipLevel>,
 }
 
 impl Texture {
-    pub fn new(file_path: &str) -> Texture {
+    pub fn load(file_path: &str) -> Result<Texture, TextureError> {
         let img = ImageReader::open(file_path)
-            .expect("Failed to open image file")
+            .map_err(|source| TextureError::Open { path: file_path.to_string(), source })?
             .decode()
-            .expect("Failed to decode image");
+            .map_err(|source| TextureError::Decode { path: file_path.to_string(), source })?;
 
-        let resized_img = resize(&img, 128, 128, image::imageops::FilterType::Nearest);
-        let resized_img = DynamicImage::ImageRgba8(resized_img);
-        let (width, height) = resized_img.dimensions();
+        let (width, height) = img.dimensions();
+        let texels = Self::load_texels(&img, width, height);
 
-        let color_array = Self::load_array(&resized_img, width, height);
+        Ok(Self::from_level(MipLevel { width, height, texels }))
+    }
+
+    // Tablero magenta y negro, imposible de confundir con una textura real
+    pub fn placeholder() -> Texture {
+        let texels = (0..PLACEHOLDER_SIZE)
+            .flat_map(|x| (0..PLACEHOLDER_SIZE).map(move |y| (x, y)))
+            .map(|(x, y)| {
+                if (x / PLACEHOLDER_CHECKER + y / PLACEHOLDER_CHECKER) % 2 == 0 {
+                    0xffff00ff
+                } else {
+                    0xff000000
+                }
+            })
+            .collect();
+
+        Self::from_level(MipLevel { width: PLACEHOLDER_SIZE, height: PLACEHOLDER_SIZE, texels })
+    }
+
+    fn from_level(base: MipLevel) -> Texture {
+        let (width, height) = (base.width, base.height);
+        let mut mips = vec![base];
+        while let Some(last) = mips.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            mips.push(next);
+        }
 
-        Texture { width, height, color_array }
+        Texture { width, height, mips }
     }
 
-    fn load_array(img: &DynamicImage, width: u32, height: u32) -> Vec<Vec<u32>> {
-        let mut color_array = vec![vec![0; height as usize]; width as usize];
+    fn load_texels(img: &DynamicImage, width: u32, height: u32) -> Vec<u32> {
+        let mut texels = vec![0; width as usize * height as usize];
 
         for (x, y, pixel) in img.pixels() {
-            let rgb = pixel.to_rgb();
-            let color = ((rgb[0] as u32) << 16) | ((rgb[1] as u32) << 8) | (rgb[2] as u32);
-            color_array[x as usize][y as usize] = color;
+            let [r, g, b, a] = pixel.to_rgba().0;
+            let color = ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+            texels[x as usize * height as usize + y as usize] = color;
+        }
+
+        texels
+    }
+
+    // Elige el nivel cuyo tamaño se acerca más al tamaño en pantalla sin quedar por debajo,
+    // así las paredes lejanas no tiemblan al saltarse texels
+    pub fn mip_level(&self, screen_size: f32) -> usize {
+        if screen_size <= 0.0 {
+            return self.mips.len() - 1;
+        }
+        let ratio = self.width.max(self.height) as f32 / screen_size;
+        if ratio <= 1.0 {
+            return 0;
         }
+        (ratio.log2().floor() as usize).min(self.mips.len() - 1)
+    }
+
+    // Franja vertical en la coordenada normalizada `u`, para muestrear una columna entera
+    pub fn strip(&self, u: f32, level: usize, filter: Filter) -> TextureStrip<'_> {
+        self.mips[level.min(self.mips.len() - 1)].strip(u.clamp(0.0, 1.0), filter)
+    }
 
-        color_array
+    // `u` y `v` son coordenadas normalizadas en [0, 1)
+    pub fn sample(&self, u: f32, v: f32, level: usize, filter: Filter) -> u32 {
+        self.strip(u, level, filter).sample(v)
     }
+}
+
+struct WallTextures {
+    default: Arc<Texture>,
+    faces: [Option<Arc<Texture>>; 4],
+    // Alto de la pared en múltiplos del alto normal
+    height: f32,
+}
+
+#[derive(Default)]
+pub struct TextureRegistry {
+    walls: HashMap<char, WallTextures>,
+    floors: HashMap<char, Arc<Texture>>,
+}
+
+impl TextureRegistry {
+    // Cada línea del manifiesto asocia un glifo del mapa con su textura, y opcionalmente
+    // una textura distinta por cara y un alto distinto del normal:
+    //   - assets/wall.jpg
+    //   g assets/station.jpg north=assets/wall.jpg height=1.5
+    // Las líneas que empiezan con `floor` asignan la textura de un piso especial, que no
+    // tiene caras ni alto:
+    //   floor ~ assets/floor_hazard.png
+    // Las líneas vacías y las que empiezan con `#` se ignoran.
+    // Las texturas que falten se reemplazan por el tablero y quedan anotadas en
+    // `take_load_errors`; solo un manifiesto ilegible hace fallar la carga.
+    pub fn load(manifest_path: &str) -> Result<Self, TextureError> {
+        let file = File::open(manifest_path)
+            .map_err(|source| TextureError::Open { path: manifest_path.to_string(), source })?;
+        let reader = BufReader::new(file);
+        let mut loaded: HashMap<String, Arc<Texture>> = HashMap::new();
+        let mut load = |path: &str| {
+            loaded
+                .entry(path.to_string())
+                .or_insert_with(|| load_texture(path))
+                .clone()
+        };
+
+        let mut walls = HashMap::new();
+        let mut floors = HashMap::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line
+                .map_err(|source| TextureError::Open { path: manifest_path.to_string(), source })?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |message: &str| TextureError::Manifest {
+                path: manifest_path.to_string(),
+                line: number + 1,
+                message: message.to_string(),
+            };
 
-    pub fn get_pixel_color(&self, x: u32, y: u32) -> u32 {
-        if x < self.width && y < self.height {
-            self.color_array[x as usize][y as usize]
-        } else {
-            0x000000
+            let mut fields = line.split_whitespace().peekable();
+            let is_floor = fields.next_if_eq(&"floor").is_some();
+            let glyph = fields.next().ok_or_else(|| invalid("missing glyph"))?;
+            let mut chars = glyph.chars();
+            let (Some(glyph), None) = (chars.next(), chars.next()) else {
+                return Err(invalid("glyph must be a single character"));
+            };
+            let path = fields.next().ok_or_else(|| invalid("missing texture path"))?;
+
+            if is_floor {
+                if fields.next().is_some() {
+                    return Err(invalid("floor entries take only a glyph and a texture path"));
+                }
+                floors.insert(glyph, load(path));
+                continue;
+            }
+
+            let mut faces: [Option<Arc<Texture>>; 4] = Default::default();
+            let mut height = 1.0;
+            for field in fields {
+                let (name, value) = field
+                    .split_once('=')
+                    .ok_or_else(|| invalid("expected <face>=<texture path>"))?;
+                if name == "height" {
+                    height = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|height| height.is_finite() && *height > 0.0)
+                        .ok_or_else(|| invalid("height must be a positive finite number"))?;
+                    continue;
+                }
+                let face = Face::from_name(name).ok_or_else(|| invalid("unknown face"))?;
+                faces[face as usize] = Some(load(value));
+            }
+
+            walls.insert(glyph, WallTextures { default: load(path), faces, height });
         }
+
+        Ok(TextureRegistry { walls, floors })
+    }
+
+    // Los glifos sin entrada en el manifiesto tienen el alto normal
+    pub fn height(&self, glyph: char) -> f32 {
+        self.walls.get(&glyph).map_or(1.0, |textures| textures.height)
     }
-}
\ No newline at end of file
+
+    pub fn get(&self, glyph: char, face: Face) -> Option<&Texture> {
+        self.walls.get(&glyph).map(|textures| {
+            textures.faces[face as usize].as_deref().unwrap_or(&textures.default)
+        })
+    }
+
+    pub fn floor(&self, glyph: char) -> Option<&Texture> {
+        self.floors.get(&glyph).map(|texture| texture.as_ref())
+    }
+}
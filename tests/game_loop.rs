@@ -0,0 +1,94 @@
+use nalgebra_glm::Vec2;
+use rust_maze3d::framebuffer::Framebuffer;
+use rust_maze3d::game::run;
+use rust_maze3d::platform::{Input, Key, Platform, ScriptedPlatform};
+
+// Enter en el título, `walk` cuadros caminando hacia adelante, Escape y unos cuadros de
+// sobra para poder distinguir un Escape de un guion que simplemente se acabó
+fn walk_then_escape(walk: usize) -> ScriptedPlatform {
+    let mut platform = ScriptedPlatform::new([vec![Key::Enter]]);
+    platform.hold(&[Key::W], walk);
+    platform.hold(&[Key::Escape], 1);
+    platform.hold(&[], 5);
+    platform
+}
+
+#[test]
+fn holding_w_moves_the_player_and_presents_frames() {
+    let mut platform = walk_then_escape(10);
+    let player = run(&mut platform, false).expect("the level should load");
+
+    assert_eq!(platform.frames.len(), 10);
+    let (width, height) = platform.size;
+    assert!(platform.frames.iter().all(|frame| frame.len() == width * height));
+    assert_ne!(platform.frames.first(), platform.frames.last());
+
+    let moved = (player.pos - Vec2::new(150.0, 150.0)).norm();
+    assert!(moved > 20.0, "the player only moved {moved} pixels");
+}
+
+#[test]
+fn escape_ends_the_run_before_the_script_does() {
+    let mut platform = walk_then_escape(0);
+    run(&mut platform, false).expect("the level should load");
+
+    assert!(platform.frames.is_empty());
+    assert!(platform.is_open(), "the run should stop at Escape, not when the script ran out");
+    assert!(!platform.title.is_empty());
+}
+
+#[test]
+fn tiny_windows_draw_the_hud_without_overflowing() {
+    for size in [(200, 150), (1, 1)] {
+        let mut platform = walk_then_escape(3);
+        platform.size = size;
+        run(&mut platform, false).expect("the level should load");
+
+        assert_eq!(platform.frames.len(), 3);
+        assert!(platform.frames.iter().all(|frame| frame.len() == size.0 * size.1));
+    }
+}
+
+// Presenta normalmente los primeros `frames` cuadros y después falla, como una ventana rota
+struct FailingPlatform {
+    inner: ScriptedPlatform,
+    frames: usize,
+}
+
+impl Platform for FailingPlatform {
+    fn is_open(&self) -> bool {
+        self.inner.is_open()
+    }
+
+    fn poll_input(&mut self) -> Input {
+        self.inner.poll_input()
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.inner.size()
+    }
+
+    fn present(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        if self.inner.frames.len() >= self.frames {
+            return Err("window lost".to_string());
+        }
+        self.inner.present(framebuffer)
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.inner.set_title(title);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.inner.set_cursor_visible(visible);
+    }
+}
+
+#[test]
+fn a_failed_present_ends_the_run_instead_of_panicking() {
+    let mut platform = FailingPlatform { inner: walk_then_escape(10), frames: 2 };
+    run(&mut platform, false).expect("the level should load");
+
+    assert_eq!(platform.inner.frames.len(), 2);
+    assert!(platform.is_open(), "the run should stop at the failed frame");
+}